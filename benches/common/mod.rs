@@ -0,0 +1,101 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+#![allow(dead_code)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Wraps the system allocator to count allocations and the bytes they hold.
+pub struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+/// Returns the number of bytes currently allocated.
+pub fn live_bytes() -> usize {
+    LIVE_BYTES.load(Ordering::Relaxed)
+}
+
+/// Runs `f` once and returns its result with the number of allocations it made.
+pub fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let start = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - start)
+}
+
+/// Runs `f` `iterations` times and prints the average time and allocations per run.
+pub fn bench<T>(name: &str, iterations: u32, mut f: impl FnMut() -> T) {
+    for _ in 0..iterations.div_ceil(10) {
+        black_box(f());
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+
+    println!(
+        "{name:<48} {:>12.0} ns/iter {:>10} allocs/iter",
+        elapsed.as_nanos() as f64 / iterations as f64,
+        allocations / iterations as usize
+    );
+}
+
+/// Like [`bench`], but passes `f` a fresh input from `setup`, which is neither timed nor
+/// counted.
+pub fn bench_with_input<I, T>(
+    name: &str,
+    iterations: u32,
+    mut setup: impl FnMut() -> I,
+    mut f: impl FnMut(I) -> T,
+) {
+    let mut elapsed = Duration::ZERO;
+    let mut allocations = 0;
+    for _ in 0..iterations {
+        let input = setup();
+        let start_allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        let output = black_box(f(black_box(input)));
+        elapsed += start.elapsed();
+        allocations += ALLOCATIONS.load(Ordering::Relaxed) - start_allocations;
+        drop(output);
+    }
+
+    println!(
+        "{name:<48} {:>12.0} ns/iter {:>10} allocs/iter",
+        elapsed.as_nanos() as f64 / iterations as f64,
+        allocations / iterations as usize
+    );
+}
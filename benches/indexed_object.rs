@@ -0,0 +1,77 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+//! Finds the object size from which an `IndexedObject` lookup beats the linear search of
+//! `ObjectAsVec`, and how many lookups it takes to pay for building the index.
+//!
+//! Linear search is as fast as the default hasher for objects with up to 8 keys, while
+//! `FxBuildHasher` is faster from 4 keys. Building the index costs about twice as much as
+//! cloning the object, and pays off after roughly 40 to 70 lookups at any size.
+
+mod common;
+
+use jmap_tools::{IndexedObject, Key, Null, ObjectAsVec, Value};
+use std::hint::black_box;
+
+fn object(len: usize) -> ObjectAsVec<'static, Null, Null> {
+    (0..len)
+        .map(|n| {
+            (
+                Key::Owned(format!("property{n}")),
+                Value::Number((n as u64).into()),
+            )
+        })
+        .collect()
+}
+
+fn main() {
+    for len in [4, 8, 16, 32, 64, 128, 256] {
+        let object = object(len);
+        let keys = (0..len)
+            .map(|n| Key::Owned(format!("property{n}")))
+            .collect::<Vec<_>>();
+        let lookups = 100_000 / len as u32;
+
+        common::bench(
+            &format!("{len:>3} keys: ObjectAsVec::get x{len}"),
+            lookups,
+            || keys.iter().filter(|key| object.get(key).is_some()).count(),
+        );
+
+        let indexed: IndexedObject<'_, Null, Null> = object.clone().into();
+        common::bench(
+            &format!("{len:>3} keys: IndexedObject::get x{len}"),
+            lookups,
+            || keys.iter().filter(|key| indexed.get(key).is_some()).count(),
+        );
+        common::bench(
+            &format!("{len:>3} keys: IndexedObject::new"),
+            lookups,
+            || IndexedObject::new(black_box(object.clone())),
+        );
+
+        #[cfg(feature = "fast_hash")]
+        {
+            let indexed: jmap_tools::FastIndexedObject<'_, Null, Null> = object.clone().into();
+            common::bench(
+                &format!("{len:>3} keys: FastIndexedObject::get x{len}"),
+                lookups,
+                || keys.iter().filter(|key| indexed.get(key).is_some()).count(),
+            );
+            common::bench(
+                &format!("{len:>3} keys: FastIndexedObject::from"),
+                lookups,
+                || jmap_tools::FastIndexedObject::<'_, Null, Null>::from(black_box(object.clone())),
+            );
+        }
+
+        common::bench(
+            &format!("{len:>3} keys: ObjectAsVec::clone"),
+            lookups,
+            || black_box(&object).clone(),
+        );
+    }
+}
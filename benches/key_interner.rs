@@ -0,0 +1,45 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+//! Compares the memory held by many similar records before and after interning their keys.
+
+mod common;
+
+use jmap_tools::{KeyInterner, Null, Value};
+
+const RECORDS: usize = 10_000;
+
+fn records() -> Value<'static, Null, Null> {
+    let json = (0..RECORDS)
+        .map(|id| {
+            format!(
+                r#"{{"id": "M{id}", "threadId": "T{id}", "mailboxIds": {{"inbox": true}},
+                "keywords": {{"$seen": true, "$flagged": false}}, "receivedAt": "2024-01-01T00:00:00Z",
+                "size": {id}, "hasAttachment": false, "preview": "Hello"}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    Value::parse_json(&format!("[{json}]"))
+        .unwrap()
+        .into_owned()
+}
+
+fn main() {
+    let baseline = common::live_bytes();
+    let mut value = records();
+    let owned = common::live_bytes() - baseline;
+
+    let mut interner = KeyInterner::new();
+    let (_, allocations) = common::count_allocations(|| interner.intern_keys(&mut value));
+    let interned = common::live_bytes() - baseline;
+
+    println!("{RECORDS} records with owned keys:    {owned:>10} bytes");
+    println!(
+        "{RECORDS} records with interned keys: {interned:>10} bytes ({} distinct keys, {allocations} allocations)",
+        interner.len()
+    );
+}
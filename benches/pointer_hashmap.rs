@@ -0,0 +1,95 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+//! Counts the allocations made by pointer lookups in a `HashMap`-backed structure, compared
+//! with looking up a `String` built from each segment.
+
+mod common;
+
+use jmap_tools::{
+    JsonPointer, JsonPointerHandler, JsonPointerItem, JsonPointerIter, Key, Null, Value,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+struct Leaf(u64);
+
+impl<'x> JsonPointerHandler<'x, Null, Null> for Leaf {
+    fn eval_jptr<'y>(
+        &'y self,
+        mut pointer: JsonPointerIter<'_, Null>,
+        results: &mut Vec<Cow<'y, Value<'x, Null, Null>>>,
+    ) {
+        if pointer.next().is_none() {
+            results.push(self.to_value());
+        }
+    }
+
+    fn patch_jptr<'y: 'x>(
+        &mut self,
+        mut pointer: JsonPointerIter<'_, Null>,
+        value: Value<'y, Null, Null>,
+    ) -> bool {
+        match (pointer.next(), value.as_u64()) {
+            (None, Some(n)) => {
+                self.0 = n;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn to_value<'y>(&'y self) -> Cow<'y, Value<'x, Null, Null>> {
+        Cow::Owned(Value::Number(self.0.into()))
+    }
+}
+
+impl TryFrom<Value<'_, Null, Null>> for Leaf {
+    type Error = ();
+
+    fn try_from(value: Value<'_, Null, Null>) -> Result<Self, Self::Error> {
+        value.as_u64().map(Leaf).ok_or(())
+    }
+}
+
+fn main() {
+    let mut map = (0..1000u64)
+        .map(|n| (n.to_string(), Leaf(n)))
+        .chain((0..1000u64).map(|n| (format!("key{n}"), Leaf(n))))
+        .collect::<HashMap<_, _>>();
+
+    let borrowed = JsonPointer::<Null>::new(vec![JsonPointerItem::Key(Key::Borrowed("key500"))]);
+    let owned = JsonPointer::<Null>::new(vec![JsonPointerItem::Key(Key::Owned("key500".into()))]);
+    let number = JsonPointer::<Null>::new(vec![JsonPointerItem::Number(500)]);
+
+    let mut results = Vec::with_capacity(1);
+    for (name, pointer) in [
+        ("eval_jptr, Borrowed key", &borrowed),
+        ("eval_jptr, Owned key", &owned),
+        ("eval_jptr, Number", &number),
+    ] {
+        common::bench(name, 100_000, || {
+            results.clear();
+            map.eval_jptr(pointer.iter(), &mut results);
+        });
+    }
+    drop(results);
+    common::bench("get with an allocated String", 100_000, || {
+        let key = std::hint::black_box("key500").to_string();
+        map.get(&key).map(|leaf| leaf.0)
+    });
+
+    common::bench("patch_jptr, Borrowed key", 100_000, || {
+        map.patch_jptr(borrowed.iter(), Value::Number(1u64.into()))
+    });
+    common::bench("patch_jptr, Number", 100_000, || {
+        map.patch_jptr(number.iter(), Value::Number(1u64.into()))
+    });
+    common::bench("get_mut with an allocated String", 100_000, || {
+        map.get_mut(&500.to_string()).is_some()
+    });
+}
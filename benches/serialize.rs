@@ -0,0 +1,36 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+//! Compares serializing a nested document directly with the `Display` implementation.
+
+mod common;
+
+use jmap_tools::{Null, Value};
+
+fn document() -> String {
+    let records = (0..100)
+        .map(|id| {
+            format!(
+                r#"{{"id": "M{id}", "subject": "Report {id}", "keywords": {{"$seen": true}},
+                "from": [{{"name": "Sender", "email": "sender{id}@example.org"}}],
+                "bodyStructure": {{"type": "multipart/mixed", "subParts": [
+                    {{"partId": "1", "type": "text/plain", "size": {id}}},
+                    {{"partId": "2", "type": "application/pdf", "size": 1024}}]}}}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(r#"{{"accountId": "a1", "state": "s1", "list": [{records}]}}"#)
+}
+
+fn main() {
+    let json = document();
+    let value = Value::<'_, Null, Null>::parse_json(&json).unwrap();
+
+    common::bench("to_string (Display)", 1_000, || value.to_string());
+    common::bench("to_json_string", 1_000, || value.to_json_string());
+    common::bench("to_json_bytes", 1_000, || value.to_json_bytes());
+}
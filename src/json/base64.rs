@@ -0,0 +1,106 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use alloc::{string::String, vec::Vec};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` using the standard base64 alphabet with padding (RFC 4648, section 4).
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let n = (b0 << 16) | (b1 << 8) | b2;
+
+        encoded.push(ALPHABET[(n >> 18) as usize & 0x3f] as char);
+        encoded.push(ALPHABET[(n >> 12) as usize & 0x3f] as char);
+        if chunk.len() > 1 {
+            encoded.push(ALPHABET[(n >> 6) as usize & 0x3f] as char);
+        } else {
+            encoded.push('=');
+        }
+        if chunk.len() > 2 {
+            encoded.push(ALPHABET[n as usize & 0x3f] as char);
+        } else {
+            encoded.push('=');
+        }
+    }
+
+    encoded
+}
+
+/// Decodes standard base64, with or without padding. Returns `None` if the input contains
+/// characters outside the alphabet or has an invalid length.
+pub(crate) fn decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    let text = text
+        .strip_suffix(b"==")
+        .or_else(|| text.strip_suffix(b"="))
+        .unwrap_or(text);
+    if text.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.chunks(4) {
+        let mut n = 0u32;
+        for &ch in chunk {
+            let value = match ch {
+                b'A'..=b'Z' => ch - b'A',
+                b'a'..=b'z' => ch - b'a' + 26,
+                b'0'..=b'9' => ch - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            n = (n << 6) | value as u32;
+        }
+        n <<= 6 * (4 - chunk.len() as u32);
+
+        decoded.push((n >> 16) as u8);
+        if chunk.len() > 2 {
+            decoded.push((n >> 8) as u8);
+        }
+        if chunk.len() > 3 {
+            decoded.push(n as u8);
+        }
+    }
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn base64_round_trip() {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0xfb, 0xff, 0xfe], "+//+"),
+        ] {
+            assert_eq!(encode(bytes), encoded);
+            assert_eq!(decode(encoded).as_deref(), Some(bytes));
+            assert_eq!(
+                decode(encoded.trim_end_matches('=')).as_deref(),
+                Some(bytes)
+            );
+        }
+
+        for invalid in ["Z", "Zm9v!", "Zm-v", "Zg==="] {
+            assert_eq!(decode(invalid), None, "{invalid}");
+        }
+    }
+}
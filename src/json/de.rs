@@ -8,13 +8,16 @@ use super::value::Value;
 use crate::json::key::{self, Key};
 use crate::json::object_vec::ObjectAsVec;
 use crate::{Element, Property};
+use alloc::borrow::Cow;
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
 use serde::de::{Deserialize, DeserializeSeed, MapAccess, SeqAccess, Visitor};
-use std::borrow::Cow;
 
 #[derive(Clone, Default)]
 pub(crate) struct DeserializationContext<'x, P: Property, E: Element> {
     parent_key: Option<&'x Key<'x, P>>,
-    phantom: std::marker::PhantomData<E>,
+    // Direct string values of an object are typed once the whole object is read
+    in_object: bool,
+    phantom: core::marker::PhantomData<E>,
 }
 
 impl<'de, P: Property, E: Element<Property = P>> Deserialize<'de> for Value<'de, P, E> {
@@ -24,7 +27,8 @@ impl<'de, P: Property, E: Element<Property = P>> Deserialize<'de> for Value<'de,
     {
         DeserializationContext {
             parent_key: None,
-            phantom: std::marker::PhantomData,
+            in_object: false,
+            phantom: core::marker::PhantomData,
         }
         .deserialize(deserializer)
     }
@@ -50,7 +54,7 @@ struct ContextualVisitor<'x, P: Property, E: Element> {
 impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for ContextualVisitor<'x, P, E> {
     type Value = Value<'de, P, E>;
 
-    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn expecting(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         formatter.write_str("any valid JSON value")
     }
 
@@ -94,6 +98,7 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
         if let Some(element) = self
             .context
             .parent_key
+            .filter(|_| !self.context.in_object)
             .and_then(|key| E::try_parse::<P>(key, &v))
         {
             Ok(Value::Element(element))
@@ -110,6 +115,7 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
         if let Some(element) = self
             .context
             .parent_key
+            .filter(|_| !self.context.in_object)
             .and_then(|key| E::try_parse::<P>(key, v))
         {
             Ok(Value::Element(element))
@@ -126,6 +132,7 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
         if let Some(element) = self
             .context
             .parent_key
+            .filter(|_| !self.context.in_object)
             .and_then(|key| E::try_parse::<P>(key, v))
         {
             Ok(Value::Element(element))
@@ -205,7 +212,8 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
     {
         DeserializationContext {
             parent_key: self.context.parent_key,
-            phantom: std::marker::PhantomData,
+            in_object: self.context.in_object,
+            phantom: core::marker::PhantomData,
         }
         .deserialize(deserializer)
     }
@@ -227,7 +235,8 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
 
         while let Some(elem) = visitor.next_element_seed(DeserializationContext {
             parent_key: self.context.parent_key,
-            phantom: std::marker::PhantomData,
+            in_object: false,
+            phantom: core::marker::PhantomData,
         })? {
             vec.push(elem);
         }
@@ -247,13 +256,33 @@ impl<'de, 'x, P: Property, E: Element<Property = P>> Visitor<'de> for Contextual
         })? {
             let value = visitor.next_value_seed(DeserializationContext {
                 parent_key: Some(&key),
-                phantom: std::marker::PhantomData,
+                in_object: true,
+                phantom: core::marker::PhantomData,
             })?;
 
             values.push((key, value));
         }
 
-        Ok(Value::Object(ObjectAsVec(values)))
+        let mut object = ObjectAsVec(values);
+        let mut elements = Vec::new();
+        for (pos, (key, value)) in object.0.iter().enumerate() {
+            if let Value::Str(text) = value
+                && let Some(element) = E::try_parse_in_object(key, text, &object)
+            {
+                elements.push((pos, element));
+            }
+        }
+        for (pos, element) in elements {
+            object.0[pos].1 = Value::Element(element);
+        }
+
+        if let Some(key) = self.context.parent_key
+            && let Some(element) = E::try_parse_object(key, &object)
+        {
+            return Ok(Value::Element(element));
+        }
+
+        Ok(Value::Object(object))
     }
 }
 
@@ -262,7 +291,7 @@ mod tests {
 
     use std::borrow::Cow;
 
-    use crate::{Null, Value};
+    use crate::{Element, Key, Null, ObjectAsVec, Value};
 
     #[test]
     fn deserialize_json_test() {
@@ -304,4 +333,69 @@ mod tests {
             &Value::Str(Cow::Borrowed("string\"_val"))
         );
     }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum TypedValue {
+        Date(String),
+        Id(String),
+    }
+
+    impl Element for TypedValue {
+        type Property = Null;
+
+        fn try_parse<P>(key: &Key<'_, Null>, value: &str) -> Option<Self> {
+            (key.to_string() == "id").then(|| TypedValue::Id(value.to_string()))
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            match self {
+                TypedValue::Date(v) | TypedValue::Id(v) => Cow::Owned(v.clone()),
+            }
+        }
+
+        fn try_parse_in_object(
+            key: &Key<'_, Null>,
+            value: &str,
+            object: &ObjectAsVec<'_, Null, Self>,
+        ) -> Option<Self> {
+            if key.to_string() == "value"
+                && object.get(&Key::Borrowed("@type")) == Some(&Value::Str("Date".into()))
+            {
+                Some(TypedValue::Date(value.to_string()))
+            } else {
+                Self::try_parse::<Null>(key, value)
+            }
+        }
+    }
+
+    #[test]
+    fn deserialize_element_in_object() {
+        let val: Value<'_, Null, TypedValue> = serde_json::from_str(
+            r#"[
+                {"value": "2024-01-01", "@type": "Date", "id": "a"},
+                {"@type": "Text", "value": "2024-01-01", "list": {"id": "b"}},
+                {"id": ["c"]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            val.get(0).get("value"),
+            &Value::Element(TypedValue::Date("2024-01-01".to_string()))
+        );
+        assert_eq!(
+            val.get(0).get("id"),
+            &Value::Element(TypedValue::Id("a".to_string()))
+        );
+        assert_eq!(val.get(0).get("@type"), &Value::Str("Date".into()));
+        assert_eq!(val.get(1).get("value"), &Value::Str("2024-01-01".into()));
+        assert_eq!(
+            val.get(1).get("list").get("id"),
+            &Value::Element(TypedValue::Id("b".to_string()))
+        );
+        assert_eq!(
+            val.get(2).get("id").get(0),
+            &Value::Element(TypedValue::Id("c".to_string()))
+        );
+    }
 }
@@ -4,7 +4,8 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
 
 use crate::json::key::Key;
 use crate::json::num::N;
@@ -12,6 +13,14 @@ use crate::{Element, Property, Value};
 use serde::Deserializer;
 use serde::de::{self, IntoDeserializer, MapAccess, SeqAccess, Visitor};
 
+impl<P: Property, E: Element> Value<'_, P, E> {
+    /// Deserializes this value into `T` by driving the deserializer directly over the tree,
+    /// avoiding a round-trip through a JSON string.
+    pub fn deserialize_into<T: de::DeserializeOwned>(&self) -> Result<T, String> {
+        T::deserialize(self).map_err(|err| err.to_string())
+    }
+}
+
 impl<'de, P: Property, E: Element> IntoDeserializer<'de, de::value::Error>
     for &'de Value<'_, P, E>
 {
@@ -278,7 +287,7 @@ impl<'de, P: Property, E: Element> Deserializer<'de> for &'de Value<'_, P, E> {
 
 // Helper struct to deserialize sequences (arrays).
 struct SeqDeserializer<'a, 'ctx, P: Property, E: Element> {
-    iter: std::slice::Iter<'a, Value<'ctx, P, E>>,
+    iter: core::slice::Iter<'a, Value<'ctx, P, E>>,
 }
 
 impl<'a, 'ctx, P: Property, E: Element> SeqDeserializer<'a, 'ctx, P, E> {
@@ -305,7 +314,7 @@ impl<'de, 'a: 'de, 'ctx: 'de, P: Property, E: Element> SeqAccess<'de>
 
 // Helper struct to deserialize maps (objects).
 struct MapDeserializer<'a, 'ctx, P: Property, E: Element> {
-    iter: std::slice::Iter<'a, (Key<'ctx, P>, Value<'ctx, P, E>)>,
+    iter: core::slice::Iter<'a, (Key<'ctx, P>, Value<'ctx, P, E>)>,
     value: Option<&'a Value<'ctx, P, E>>,
 }
 
@@ -452,6 +461,7 @@ mod tests {
 
     // Test deserialization of a map (object)
     #[test]
+    #[cfg(feature = "std")]
     fn test_deserialize_map() {
         let value: Value<'_, Null, Null> = Value::Object(
             vec![
@@ -539,4 +549,33 @@ mod tests {
         let result: Result<(), DeError> = Deserialize::deserialize(&value);
         assert!(result.is_err());
     }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Contact {
+        name: String,
+        emails: Vec<String>,
+        age: Option<u64>,
+    }
+
+    #[test]
+    fn test_deserialize_into() {
+        let value: Value<'_, Null, Null> = Value::parse_json(
+            r#"{"contact": {"name": "John", "emails": ["john@example.org"], "extra": true}}"#,
+        )
+        .unwrap();
+
+        let contact: Contact = value
+            .get(&Key::Borrowed("contact"))
+            .deserialize_into()
+            .unwrap();
+        assert_eq!(
+            contact,
+            Contact {
+                name: "John".to_string(),
+                emails: vec!["john@example.org".to_string()],
+                age: None,
+            }
+        );
+        assert!(value.deserialize_into::<Vec<String>>().is_err());
+    }
 }
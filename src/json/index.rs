@@ -5,7 +5,8 @@
  */
 
 use crate::{
-    Value,
+    JsonPointer, Value,
+    json::key::Key,
     json::value::{Element, Property},
 };
 
@@ -35,3 +36,47 @@ impl<'v, 'a: 'v, P: Property, E: Element> Index<'v, P, E> for &'a str {
         }
     }
 }
+
+impl<'v, P: Property, E: Element> Index<'v, P, E> for &Key<'_, P> {
+    #[inline]
+    fn index_into(self, v: &'v Value<'v, P, E>) -> Option<&'v Value<'v, P, E>> {
+        match v {
+            Value::Object(map) => map.get(self),
+            _ => None,
+        }
+    }
+}
+
+/// Resolves a concrete path, wildcard segments never match.
+impl<'v, P: Property, E: Element> Index<'v, P, E> for &JsonPointer<P> {
+    #[inline]
+    fn index_into(self, v: &'v Value<'v, P, E>) -> Option<&'v Value<'v, P, E>> {
+        v.resolve_pointer(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{JsonPointer, Key, Null, Value};
+
+    #[test]
+    fn index_by_key_and_pointer() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": {"b": [1, {"c": true}]}, "d": "e"}"#).unwrap();
+
+        assert_eq!(value.get(&Key::Borrowed("d")), &Value::Str("e".into()));
+        assert_eq!(
+            value.get(&Key::Owned("a".to_string())).get("b").get(0),
+            &Value::Number(1u64.into())
+        );
+        assert_eq!(value.get(&Key::Borrowed("missing")), &Value::Null);
+
+        assert_eq!(
+            value.get(&JsonPointer::parse("a/b/1/c")),
+            &Value::Bool(true)
+        );
+        assert_eq!(value.get(&JsonPointer::parse("d")), &Value::Str("e".into()));
+        assert_eq!(value.get(&JsonPointer::parse("a/*/0")), &Value::Null);
+        assert_eq!(value.get(&JsonPointer::parse("a/b/5")), &Value::Null);
+    }
+}
@@ -0,0 +1,303 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use std::collections::HashMap;
+use std::collections::hash_map::{Entry, RandomState};
+use std::hash::BuildHasher;
+
+use crate::{
+    ObjectAsVec, Value,
+    json::key::Key,
+    json::value::{Element, Property},
+};
+
+/// An `ObjectAsVec` paired with a hash index over its keys, making `get` and `contains_key`
+/// O(1). Entries keep their insertion order, except that `remove` moves the last entry into
+/// the removed one's position.
+///
+/// The object can only be modified through this wrapper so that the index stays in sync.
+/// The hasher can be replaced with a faster non-cryptographic one through `S`, such as
+/// `rustc_hash::FxBuildHasher` with the `fast_hash` feature.
+///
+/// ## Performance
+/// Building the index hashes every key once and allocates a copy of it, so it only pays off
+/// for large objects that are queried repeatedly. Small objects are usually as fast to
+/// search linearly with `ObjectAsVec::get`.
+#[derive(Debug, Clone)]
+pub struct IndexedObject<'ctx, P: Property, E: Element, S = RandomState> {
+    object: ObjectAsVec<'ctx, P, E>,
+    index: HashMap<Box<str>, usize, S>,
+    has_duplicates: bool,
+}
+
+/// An [`IndexedObject`] hashing its keys with `rustc_hash::FxBuildHasher`. It is much faster
+/// than the default SipHash on short keys, but offers no protection against collisions crafted
+/// by an attacker, so keys from untrusted input should keep the default hasher.
+#[cfg(feature = "fast_hash")]
+pub type FastIndexedObject<'ctx, P, E> = IndexedObject<'ctx, P, E, rustc_hash::FxBuildHasher>;
+
+impl<'ctx, P: Property, E: Element> IndexedObject<'ctx, P, E> {
+    pub fn new(object: ObjectAsVec<'ctx, P, E>) -> Self {
+        Self::with_hasher(object, RandomState::new())
+    }
+}
+
+impl<'ctx, P: Property, E: Element, S: BuildHasher> IndexedObject<'ctx, P, E, S> {
+    /// Builds the index using the supplied hasher. If the object contains duplicate keys,
+    /// the first occurrence is indexed, matching `ObjectAsVec::get`.
+    pub fn with_hasher(object: ObjectAsVec<'ctx, P, E>, hasher: S) -> Self {
+        let mut indexed = IndexedObject {
+            index: HashMap::with_capacity_and_hasher(object.len(), hasher),
+            object,
+            has_duplicates: false,
+        };
+        indexed.build_index();
+        indexed
+    }
+
+    fn build_index(&mut self) {
+        self.index.clear();
+        for (pos, (key, _)) in self.object.0.iter().enumerate() {
+            match self.index.entry(key.to_string().into()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(pos);
+                }
+                Entry::Occupied(_) => self.has_duplicates = true,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, key: &Key<'_, P>) -> Option<&Value<'ctx, P, E>> {
+        self.position(key).map(|pos| &self.object.0[pos].1)
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, key: &Key<'_, P>) -> Option<&mut Value<'ctx, P, E>> {
+        self.position(key).map(|pos| &mut self.object.0[pos].1)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &Key<'_, P>) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the position of the key in insertion order.
+    #[inline]
+    pub fn position(&self, key: &Key<'_, P>) -> Option<usize> {
+        self.index.get(key.to_string().as_ref()).copied()
+    }
+
+    /// Inserts a key-value pair, replacing and returning the previous value if the key was
+    /// already present.
+    pub fn insert(
+        &mut self,
+        key: impl Into<Key<'ctx, P>>,
+        value: impl Into<Value<'ctx, P, E>>,
+    ) -> Option<Value<'ctx, P, E>> {
+        let key = key.into();
+        if let Some(pos) = self.position(&key) {
+            Some(std::mem::replace(&mut self.object.0[pos].1, value.into()))
+        } else {
+            self.index.insert(key.to_string().into(), self.object.len());
+            self.object.0.push((key, value.into()));
+            None
+        }
+    }
+
+    /// Removes a key from the object, returning its value.
+    ///
+    /// The last entry is moved into the position of the removed one, so insertion order is
+    /// not preserved. If the object was built with duplicate keys, the moved entry can end up
+    /// ahead of an earlier occurrence of its key, so the whole index is rebuilt to keep
+    /// pointing at the first occurrence of every key.
+    pub fn remove(&mut self, key: &Key<'_, P>) -> Option<Value<'ctx, P, E>> {
+        let pos = self.index.remove(key.to_string().as_ref())?;
+        let last = self.object.len() - 1;
+        let (_, value) = self.object.0.swap_remove(pos);
+        if self.has_duplicates {
+            self.build_index();
+        } else if let Some((moved, _)) = self.object.0.get(pos)
+            && let Some(moved_pos) = self.index.get_mut(moved.to_string().as_ref())
+            && *moved_pos == last
+        {
+            *moved_pos = pos;
+        }
+        Some(value)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.object.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.object.is_empty()
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        self.object.iter()
+    }
+
+    #[inline]
+    pub fn as_object(&self) -> &ObjectAsVec<'ctx, P, E> {
+        &self.object
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> ObjectAsVec<'ctx, P, E> {
+        self.object
+    }
+}
+
+impl<'ctx, P: Property, E: Element, S: BuildHasher + Default> From<ObjectAsVec<'ctx, P, E>>
+    for IndexedObject<'ctx, P, E, S>
+{
+    fn from(object: ObjectAsVec<'ctx, P, E>) -> Self {
+        IndexedObject::with_hasher(object, S::default())
+    }
+}
+
+impl<'ctx, P: Property, E: Element, S> From<IndexedObject<'ctx, P, E, S>>
+    for ObjectAsVec<'ctx, P, E>
+{
+    fn from(indexed: IndexedObject<'ctx, P, E, S>) -> Self {
+        indexed.object
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedObject;
+    use crate::{Key, Null, ObjectAsVec, Value};
+
+    #[test]
+    fn indexed_object() {
+        let mut obj: IndexedObject<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("c", 3u64)
+            .into();
+
+        assert_eq!(
+            obj.get(&Key::Borrowed("b")),
+            Some(&Value::Number(2u64.into()))
+        );
+        assert!(obj.contains_key(&Key::Owned("c".to_string())));
+        assert!(!obj.contains_key(&Key::Borrowed("d")));
+
+        assert_eq!(
+            obj.insert(Key::Borrowed("b"), Value::Bool(true)),
+            Some(Value::Number(2u64.into()))
+        );
+        assert_eq!(obj.insert(Key::Borrowed("d"), Value::Null), None);
+        assert_eq!(obj.position(&Key::Borrowed("d")), Some(3));
+
+        assert_eq!(
+            obj.remove(&Key::Borrowed("a")),
+            Some(Value::Number(1u64.into()))
+        );
+        assert_eq!(obj.remove(&Key::Borrowed("a")), None);
+        assert_eq!(obj.get(&Key::Borrowed("d")), Some(&Value::Null));
+        assert_eq!(obj.position(&Key::Borrowed("d")), Some(0));
+
+        *obj.get_mut(&Key::Borrowed("c")).unwrap() = Value::Null;
+        assert_eq!(
+            obj.into_inner(),
+            ObjectAsVec::new()
+                .with_key_value("d", Value::Null)
+                .with_key_value("b", true)
+                .with_key_value("c", Value::Null)
+        );
+    }
+
+    #[test]
+    fn indexed_object_duplicate_keys() {
+        let mut object = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64);
+        object.insert_unchecked("a", 3u64);
+        object.insert_unchecked("c", 4u64);
+        object.insert_unchecked("b", 5u64);
+        let mut obj: IndexedObject<'_, Null, Null> = object.into();
+
+        // The first occurrence is indexed, as with `ObjectAsVec::get`
+        assert_eq!(obj.position(&Key::Borrowed("a")), Some(0));
+        assert_eq!(obj.position(&Key::Borrowed("b")), Some(1));
+
+        // The trailing duplicate of "b" moves ahead of the indexed one and becomes the first
+        assert_eq!(
+            obj.remove(&Key::Borrowed("a")),
+            Some(Value::Number(1u64.into()))
+        );
+        assert_eq!(obj.position(&Key::Borrowed("b")), Some(0));
+        assert_eq!(obj.position(&Key::Borrowed("a")), Some(2));
+        assert_eq!(
+            obj.get(&Key::Borrowed("a")),
+            Some(&Value::Number(3u64.into()))
+        );
+
+        assert_eq!(
+            obj.remove(&Key::Borrowed("b")),
+            Some(Value::Number(5u64.into()))
+        );
+        for (pos, (key, _)) in obj.iter().enumerate() {
+            assert_eq!(obj.position(key), Some(pos), "{key:?}");
+        }
+        assert_eq!(
+            obj.get(&Key::Borrowed("b")),
+            Some(&Value::Number(2u64.into()))
+        );
+        assert_eq!(obj.len(), 3);
+    }
+
+    #[test]
+    fn indexed_object_remove_moves_duplicate() {
+        let mut obj: IndexedObject<'_, Null, Null> = ["x", "a", "b", "a"]
+            .into_iter()
+            .enumerate()
+            .map(|(n, key)| (Key::Borrowed(key), Value::Number((n as u64).into())))
+            .collect::<ObjectAsVec<'_, Null, Null>>()
+            .into();
+
+        assert_eq!(
+            obj.remove(&Key::Borrowed("x")),
+            Some(Value::Number(0u64.into()))
+        );
+        assert_eq!(obj.position(&Key::Borrowed("a")), Some(0));
+        assert_eq!(
+            obj.get(&Key::Borrowed("a")),
+            obj.as_object().get(&Key::Borrowed("a"))
+        );
+        assert_eq!(obj.position(&Key::Borrowed("b")), Some(2));
+    }
+
+    #[cfg(feature = "fast_hash")]
+    #[test]
+    fn fast_indexed_object() {
+        use super::FastIndexedObject;
+
+        let mut obj: FastIndexedObject<'_, Null, Null> = (0..64u64)
+            .map(|n| (Key::Owned(format!("key{n}")), Value::Number(n.into())))
+            .collect::<ObjectAsVec<'_, Null, Null>>()
+            .into();
+
+        for n in 0..64u64 {
+            assert_eq!(
+                obj.position(&Key::Owned(format!("key{n}"))),
+                Some(n as usize)
+            );
+        }
+        assert_eq!(
+            obj.remove(&Key::Borrowed("key0")),
+            Some(Value::Number(0u64.into()))
+        );
+        assert_eq!(obj.position(&Key::Borrowed("key63")), Some(0));
+        assert!(!obj.contains_key(&Key::Borrowed("key64")));
+    }
+}
@@ -0,0 +1,157 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use alloc::{collections::BTreeSet, sync::Arc};
+use core::marker::PhantomData;
+
+use crate::{
+    Value,
+    json::key::Key,
+    json::value::{Element, Property},
+};
+
+/// Deduplicates the strings of keys, so that every key spelling the same string shares a
+/// single allocation through [`Key::Shared`].
+///
+/// ## Performance
+/// Objects stored as `ObjectAsVec` repeat their keys in every record, and each owned key
+/// holds its own heap allocation. Once interned, a key costs a reference count increment
+/// and every distinct string is allocated once, for as long as the interner or any key
+/// sharing it is alive. Properties carry no string and are returned unchanged. Run
+/// `cargo bench --bench key_interner` to compare the memory held by a list of records.
+#[derive(Debug, Clone)]
+pub struct KeyInterner<P: Property> {
+    strings: BTreeSet<Arc<str>>,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Property> Default for KeyInterner<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Property> KeyInterner<P> {
+    pub fn new() -> Self {
+        KeyInterner {
+            strings: BTreeSet::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns a shared key for `key`, reusing the string of a previously interned key
+    /// when there is one.
+    pub fn intern(&mut self, key: Key<'_, P>) -> Key<'static, P> {
+        match key {
+            Key::Property(word) => Key::Property(word),
+            Key::Shared(s) => Key::Shared(self.intern_shared(s)),
+            key => Key::Shared(self.intern_str(key.to_string().as_ref())),
+        }
+    }
+
+    /// Replaces the string keys of every object in `value`, at any depth, with shared keys.
+    pub fn intern_keys<E: Element>(&mut self, value: &mut Value<'_, P, E>) {
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    self.intern_keys(value);
+                }
+            }
+            Value::Object(map) => {
+                for (key, value) in map.0.iter_mut() {
+                    if !matches!(key, Key::Property(_)) {
+                        *key = self.intern(core::mem::replace(key, Key::Borrowed("")));
+                    }
+                    self.intern_keys(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of distinct strings held by the interner.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    fn intern_str(&mut self, s: &str) -> Arc<str> {
+        match self.strings.get(s) {
+            Some(shared) => shared.clone(),
+            None => {
+                let shared: Arc<str> = Arc::from(s);
+                self.strings.insert(shared.clone());
+                shared
+            }
+        }
+    }
+
+    fn intern_shared(&mut self, s: Arc<str>) -> Arc<str> {
+        match self.strings.get(&s) {
+            Some(shared) => shared.clone(),
+            None => {
+                self.strings.insert(s.clone());
+                s
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyInterner;
+    use crate::{Key, Null, ObjectAsVec, Value};
+    use std::sync::Arc;
+
+    #[test]
+    fn intern_keys() {
+        let mut interner = KeyInterner::<Null>::new();
+
+        let a = interner.intern(Key::Borrowed("mailboxIds"));
+        let b = interner.intern(Key::Owned("mailboxIds".to_string()));
+        let c = interner.intern(Key::Shared(Arc::from("keywords")));
+        assert_eq!(a, Key::Borrowed("mailboxIds"));
+        assert_eq!(c, Key::Borrowed("keywords"));
+        match (&a, &b) {
+            (Key::Shared(a), Key::Shared(b)) => assert!(Arc::ptr_eq(a, b)),
+            _ => panic!("expected shared keys"),
+        }
+        assert_eq!(interner.len(), 2);
+
+        // Many similar records end up sharing one allocation per distinct key
+        let mut value = Value::<'_, Null, Null>::Array(
+            (0..100u64)
+                .map(|id| {
+                    Value::Object(
+                        ObjectAsVec::new()
+                            .with_key_value(Key::Owned("id".to_string()), id)
+                            .with_key_value(
+                                Key::Owned("keywords".to_string()),
+                                Value::Object(
+                                    ObjectAsVec::new()
+                                        .with_key_value(Key::Owned("$seen".to_string()), true),
+                                ),
+                            ),
+                    )
+                })
+                .collect(),
+        );
+        let expected = value.clone();
+        interner.intern_keys(&mut value);
+
+        assert_eq!(value, expected);
+        assert_eq!(interner.len(), 4);
+        let Some(Key::Shared(id)) = value.get(0).as_object().and_then(|obj| obj.keys().next())
+        else {
+            panic!("expected a shared key");
+        };
+        // One reference per record plus the one held by the interner
+        assert_eq!(Arc::strong_count(id), 101);
+    }
+}
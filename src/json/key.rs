@@ -4,17 +4,27 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
+use crate::JsonPointerItem;
 use crate::json::value::Property;
+use alloc::borrow::Cow;
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    sync::Arc,
+};
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
 use serde::de::{self, DeserializeSeed, Visitor};
 use serde::{Serialize, Serializer};
-use std::borrow::Cow;
-use std::fmt;
 
-#[derive(Debug, Clone, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone)]
 pub enum Key<'x, P: Property> {
     Property(P),
     Borrowed(&'x str),
     Owned(String),
+    /// A string shared between keys, as produced by [`KeyInterner`](crate::KeyInterner).
+    Shared(Arc<str>),
 }
 
 pub(crate) struct DeserializationContext<'x, P: Property> {
@@ -87,20 +97,38 @@ impl<P: Property> PartialEq for Key<'_, P> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Key::Property(k1), Key::Property(k2)) => k1 == k2,
-            (Key::Property(k1), Key::Borrowed(k2)) => k1.to_cow() == *k2,
-            (Key::Property(k1), Key::Owned(k2)) => k1.to_cow() == k2.as_str(),
-            (Key::Owned(k1), Key::Owned(k2)) => k1 == k2,
-            (Key::Owned(k1), Key::Borrowed(k2)) => k1 == k2,
-            (Key::Owned(k1), Key::Property(k2)) => k1.as_str() == k2.to_cow(),
-            (Key::Borrowed(k1), Key::Borrowed(k2)) => k1 == k2,
-            (Key::Borrowed(k1), Key::Owned(k2)) => k1 == k2,
-            (Key::Borrowed(k1), Key::Property(k2)) => *k1 == k2.to_cow(),
+            _ => self.to_string() == other.to_string(),
         }
     }
 }
 
 impl<P: Property> Eq for Key<'_, P> {}
 
+/// Keys are hashed by their string form, consistently with `PartialEq`, so a property, a
+/// borrowed, an owned and a shared key spelling the same string hash identically.
+impl<P: Property> Hash for Key<'_, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_string().as_ref().hash(state);
+    }
+}
+
+/// Keys are ordered by their string form, consistently with `PartialEq`, so a property and a
+/// string spelling the same key compare equal.
+impl<P: Property> Ord for Key<'_, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Key::Property(k1), Key::Property(k2)) if k1 == k2 => Ordering::Equal,
+            _ => self.to_string().cmp(&other.to_string()),
+        }
+    }
+}
+
+impl<P: Property> PartialOrd for Key<'_, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl<P: Property> PartialEq<&str> for Key<'_, P> {
     fn eq(&self, other: &&str) -> bool {
         self.to_string() == *other
@@ -116,11 +144,14 @@ impl<'x, P: Property> From<&'x str> for Key<'x, P> {
     }
 }
 
+/// Converts without copying, except for [`Key::Shared`] keys, whose string is copied into a new
+/// allocation as a `Cow` can't hold an `Arc<str>`.
 impl<'x, P: Property> From<Key<'x, P>> for Cow<'x, str> {
     fn from(s: Key<'x, P>) -> Self {
         match s {
             Key::Borrowed(s) => Cow::Borrowed(s),
             Key::Owned(s) => Cow::Owned(s),
+            Key::Shared(s) => Cow::Owned(s.as_ref().to_owned()),
             Key::Property(word) => word.to_cow(),
         }
     }
@@ -140,6 +171,7 @@ impl<P: Property> Key<'_, P> {
         match self {
             Key::Borrowed(s) => Cow::Borrowed(s),
             Key::Owned(s) => Cow::Borrowed(s.as_str()),
+            Key::Shared(s) => Cow::Borrowed(s),
             Key::Property(word) => word.to_cow(),
         }
     }
@@ -148,6 +180,7 @@ impl<P: Property> Key<'_, P> {
         match self {
             Key::Borrowed(s) => s.to_owned(),
             Key::Owned(s) => s,
+            Key::Shared(s) => s.as_ref().to_owned(),
             Key::Property(word) => word.to_cow().into_owned(),
         }
     }
@@ -163,6 +196,7 @@ impl<P: Property> Key<'_, P> {
         match self {
             Key::Borrowed(s) => Key::Owned(s.to_owned()),
             Key::Owned(s) => Key::Owned(s),
+            Key::Shared(s) => Key::Shared(s),
             Key::Property(word) => Key::Property(word),
         }
     }
@@ -171,6 +205,7 @@ impl<P: Property> Key<'_, P> {
         match self {
             Key::Borrowed(s) => Key::Owned(s.to_string()),
             Key::Owned(s) => Key::Owned(s.clone()),
+            Key::Shared(s) => Key::Shared(s.clone()),
             Key::Property(word) => Key::Property(word.clone()),
         }
     }
@@ -186,9 +221,114 @@ impl<P: Property> Key<'_, P> {
         match self {
             Key::Borrowed(s) => Some(s),
             Key::Owned(s) => Some(s.as_str()),
+            Key::Shared(s) => Some(s),
             Key::Property(_) => None,
         }
     }
+
+    /// Returns the key as an owned `JsonPointerItem::Key` segment.
+    pub fn to_pointer_item(&self) -> JsonPointerItem<P> {
+        JsonPointerItem::Key(self.to_owned())
+    }
+
+    /// Returns true if a pointer segment addresses this key: keys have to resolve to the
+    /// same string, numbers to the same digits, and wildcards match any key.
+    pub fn matches_pointer_item(&self, item: &JsonPointerItem<P>) -> bool {
+        match item {
+            JsonPointerItem::Key(key) => self == key,
+            JsonPointerItem::Number(n) => self.to_string() == n.to_string(),
+            JsonPointerItem::NumberFromEnd(n) => {
+                self.to_string().strip_prefix('-') == Some(n.to_string().as_str())
+            }
+            JsonPointerItem::Wildcard | JsonPointerItem::RecursiveWildcard => true,
+            JsonPointerItem::Root => false,
+        }
+    }
+
+    /// Returns true if both keys resolve to the same string, ignoring ASCII case.
+    pub fn eq_ignore_ascii_case(&self, other: &Key<'_, P>) -> bool {
+        match (self, other) {
+            (Key::Property(k1), Key::Property(k2)) if k1 == k2 => true,
+            _ => self
+                .to_string()
+                .eq_ignore_ascii_case(other.to_string().as_ref()),
+        }
+    }
+
+    /// Returns true if the key string starts with `prefix`. String keys are checked in
+    /// place, properties are checked against their `to_cow` form.
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        match self {
+            Key::Borrowed(s) => s.starts_with(prefix),
+            Key::Owned(s) => s.starts_with(prefix),
+            Key::Shared(s) => s.starts_with(prefix),
+            Key::Property(word) => word.to_cow().starts_with(prefix),
+        }
+    }
+
+    /// Returns true if the key string ends with `suffix`.
+    pub fn ends_with(&self, suffix: &str) -> bool {
+        match self {
+            Key::Borrowed(s) => s.ends_with(suffix),
+            Key::Owned(s) => s.ends_with(suffix),
+            Key::Shared(s) => s.ends_with(suffix),
+            Key::Property(word) => word.to_cow().ends_with(suffix),
+        }
+    }
+
+    /// Returns true if the key string contains `pattern`.
+    pub fn contains(&self, pattern: &str) -> bool {
+        match self {
+            Key::Borrowed(s) => s.contains(pattern),
+            Key::Owned(s) => s.contains(pattern),
+            Key::Shared(s) => s.contains(pattern),
+            Key::Property(word) => word.to_cow().contains(pattern),
+        }
+    }
+}
+
+/// A `Key` wrapper whose `Eq`, `Ord` and `Hash` implementations ignore ASCII case,
+/// allowing it to be used in case-insensitive `HashMap`s and `BTreeMap`s.
+#[derive(Debug, Clone)]
+pub struct CaseInsensitiveKey<'x, P: Property>(pub Key<'x, P>);
+
+impl<P: Property> PartialEq for CaseInsensitiveKey<'_, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl<P: Property> Eq for CaseInsensitiveKey<'_, P> {}
+
+impl<P: Property> PartialOrd for CaseInsensitiveKey<'_, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Property> Ord for CaseInsensitiveKey<'_, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .to_string()
+            .bytes()
+            .map(|b| b.to_ascii_lowercase())
+            .cmp(other.0.to_string().bytes().map(|b| b.to_ascii_lowercase()))
+    }
+}
+
+impl<P: Property> Hash for CaseInsensitiveKey<'_, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in self.0.to_string().bytes() {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+        state.write_u8(0xff);
+    }
+}
+
+impl<'x, P: Property> From<Key<'x, P>> for CaseInsensitiveKey<'x, P> {
+    fn from(key: Key<'x, P>) -> Self {
+        CaseInsensitiveKey(key)
+    }
 }
 
 impl<'x, P: Property> From<P> for Key<'x, P> {
@@ -196,3 +336,159 @@ impl<'x, P: Property> From<P> for Key<'x, P> {
         Key::Property(word)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CaseInsensitiveKey, Key};
+    use crate::{JsonPointerItem, Property};
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, HashSet};
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum TestProp {
+        MessageId,
+        MySetFoo,
+    }
+
+    impl Property for TestProp {
+        fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+            match value {
+                "messageId" => Some(TestProp::MessageId),
+                "mySet:foo" => Some(TestProp::MySetFoo),
+                _ => None,
+            }
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            match self {
+                TestProp::MessageId => "messageId".into(),
+                TestProp::MySetFoo => "mySet:foo".into(),
+            }
+        }
+    }
+
+    #[test]
+    fn eq_ignore_ascii_case() {
+        let keys: [Key<'_, TestProp>; 3] = [
+            Key::Property(TestProp::MessageId),
+            Key::Borrowed("MESSAGEID"),
+            Key::Owned("MessageId".to_string()),
+        ];
+
+        for k1 in &keys {
+            for k2 in &keys {
+                assert!(k1.eq_ignore_ascii_case(k2), "{k1:?} {k2:?}");
+            }
+            assert!(!k1.eq_ignore_ascii_case(&Key::Borrowed("message-id")));
+        }
+    }
+
+    #[test]
+    fn case_insensitive_key() {
+        let mut set = HashSet::new();
+        assert!(set.insert(CaseInsensitiveKey(Key::Property(TestProp::MessageId))));
+        assert!(!set.insert(CaseInsensitiveKey(Key::Borrowed("MESSAGEID"))));
+        assert!(!set.insert(CaseInsensitiveKey(Key::Owned("messageid".to_string()))));
+        assert!(set.insert(CaseInsensitiveKey(Key::Borrowed("subject"))));
+        assert_eq!(set.len(), 2);
+
+        let mut map = BTreeMap::new();
+        map.insert(CaseInsensitiveKey(Key::<TestProp>::Borrowed("B")), 1);
+        map.insert(CaseInsensitiveKey(Key::Borrowed("a")), 2);
+        map.insert(CaseInsensitiveKey(Key::Borrowed("b")), 3);
+        assert_eq!(
+            map.into_iter()
+                .map(|(k, v)| (k.0.into_string(), v))
+                .collect::<Vec<_>>(),
+            vec![("a".to_string(), 2), ("B".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn key_pointer_item() {
+        for key in [
+            Key::Property(TestProp::MessageId),
+            Key::Borrowed("messageId"),
+            Key::Owned("messageId".to_string()),
+        ] {
+            let item = key.to_pointer_item();
+            assert_eq!(item, JsonPointerItem::Key(key.to_owned()), "{key:?}");
+            assert_eq!(JsonPointerItem::from(key.to_owned()), item, "{key:?}");
+            assert!(key.matches_pointer_item(&item), "{key:?}");
+            assert_eq!(item.into_key(), Some(key.to_owned()), "{key:?}");
+        }
+
+        let key = Key::<TestProp>::Borrowed("1");
+        assert!(key.matches_pointer_item(&JsonPointerItem::Number(1)));
+        assert!(key.matches_pointer_item(&JsonPointerItem::Wildcard));
+        assert!(!key.matches_pointer_item(&JsonPointerItem::Number(2)));
+        assert!(!key.matches_pointer_item(&JsonPointerItem::Root));
+        assert!(!key.matches_pointer_item(&JsonPointerItem::Key("2".into())));
+        assert_eq!(JsonPointerItem::<TestProp>::Number(1).into_key(), None);
+    }
+
+    #[test]
+    fn key_string_predicates() {
+        for key in [
+            Key::Property(TestProp::MySetFoo),
+            Key::Borrowed("mySet:foo"),
+            Key::Owned("mySet:foo".to_string()),
+        ] {
+            assert!(key.starts_with("mySet:"), "{key:?}");
+            assert!(key.starts_with(""), "{key:?}");
+            assert!(!key.starts_with("myset:"), "{key:?}");
+            assert!(key.ends_with(":foo"), "{key:?}");
+            assert!(!key.ends_with("mySet"), "{key:?}");
+            assert!(key.contains("Set:f"), "{key:?}");
+            assert!(key.contains("mySet:foo"), "{key:?}");
+            assert!(!key.contains("bar"), "{key:?}");
+        }
+
+        let key = Key::Property(TestProp::MessageId);
+        assert!(!key.starts_with("mySet:"));
+        assert!(key.ends_with("Id"));
+    }
+
+    #[test]
+    fn pointer_item_ordering() {
+        let mut items: Vec<JsonPointerItem<TestProp>> = vec![
+            JsonPointerItem::Number(10),
+            JsonPointerItem::Key(Key::Property(TestProp::MySetFoo)),
+            JsonPointerItem::NumberFromEnd(1),
+            JsonPointerItem::Key(Key::Owned("zeta".to_string())),
+            JsonPointerItem::RecursiveWildcard,
+            JsonPointerItem::Number(2),
+            JsonPointerItem::Key(Key::Property(TestProp::MessageId)),
+            JsonPointerItem::Root,
+            JsonPointerItem::Key(Key::Borrowed("alpha")),
+            JsonPointerItem::Wildcard,
+        ];
+        items.sort();
+
+        assert_eq!(
+            items,
+            vec![
+                JsonPointerItem::Root,
+                JsonPointerItem::Wildcard,
+                JsonPointerItem::RecursiveWildcard,
+                JsonPointerItem::Key(Key::Borrowed("alpha")),
+                JsonPointerItem::Key(Key::Property(TestProp::MessageId)),
+                JsonPointerItem::Key(Key::Property(TestProp::MySetFoo)),
+                JsonPointerItem::Key(Key::Owned("zeta".to_string())),
+                JsonPointerItem::Number(2),
+                JsonPointerItem::Number(10),
+                JsonPointerItem::NumberFromEnd(1),
+            ]
+        );
+
+        let property = JsonPointerItem::Key(Key::Property(TestProp::MessageId));
+        let owned = JsonPointerItem::Key(Key::Owned("messageId".to_string()));
+        assert_eq!(property, owned);
+        assert_eq!(property.cmp(&owned), std::cmp::Ordering::Equal);
+
+        let mut map = BTreeMap::new();
+        map.insert(property, 1);
+        map.insert(owned, 2);
+        assert_eq!(map.len(), 1);
+    }
+}
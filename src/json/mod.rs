@@ -4,13 +4,21 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
+pub(crate) mod base64;
 pub(crate) mod de;
 pub(crate) mod deserializer;
 pub(crate) mod index;
+#[cfg(feature = "std")]
+pub(crate) mod indexed;
+pub(crate) mod interner;
 pub(crate) mod key;
 pub(crate) mod num;
 pub(crate) mod object_vec;
 #[cfg(feature = "rkyv")]
 pub(crate) mod rkyv;
+pub(crate) mod schema;
 pub(crate) mod ser;
+pub(crate) mod serializer;
+#[cfg(feature = "std")]
+pub(crate) mod stream;
 pub(crate) mod value;
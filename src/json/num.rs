@@ -4,6 +4,10 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
+#[cfg(feature = "raw_number")]
+use alloc::{boxed::Box, string::ToString};
+use core::cmp::Ordering;
+use core::fmt::{self, Display};
 use core::hash::{Hash, Hasher};
 
 /// Represents a JSON number, whether integer or floating point.
@@ -28,7 +32,88 @@ pub(crate) enum N {
     Float(f64),
 }
 
+/// A JSON number kept as its original text, for values that no integer or `f64` can hold
+/// exactly. It is serialized verbatim, so high-precision decimals round-trip unchanged
+/// where a [`Number`] would approximate them. Unlike `Number`, it owns its text and is not
+/// `Copy`.
+///
+/// It is not connected to [`Value`](crate::Value): the parser still reads every number into a
+/// `Number`, so a `RawNumber` has to be built with [`RawNumber::from_decimal_str`] and
+/// serialized on its own.
+#[cfg(feature = "raw_number")]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RawNumber {
+    text: Box<str>,
+    number: Number,
+}
+
+#[cfg(feature = "raw_number")]
+impl RawNumber {
+    /// Keeps `s` as a number. Returns `None` if `s` is not a valid JSON number or is out of
+    /// the `f64` range.
+    pub fn from_decimal_str(s: &str) -> Option<RawNumber> {
+        Number::from_decimal_str(s).map(|number| RawNumber {
+            text: s.into(),
+            number,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number as a [`Number`], approximating it as an `f64` unless
+    /// [`RawNumber::is_exact`].
+    pub fn to_number(&self) -> Number {
+        self.number
+    }
+
+    /// Returns true if a [`Number`] holds this value exactly and formats back to the same text.
+    pub fn is_exact(&self) -> bool {
+        self.number.to_string() == *self.text
+    }
+}
+
+#[cfg(feature = "raw_number")]
+impl fmt::Debug for RawNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RawNumber({})", self.text)
+    }
+}
+
+#[cfg(feature = "raw_number")]
+impl Display for RawNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
 impl Number {
+    /// Parses a JSON number, keeping it exact whenever possible: integers that fit a `u64` or
+    /// a negative `i64` are stored as such, and other values as an `f64` when it formats back
+    /// to the same text. Otherwise the number is approximated as an `f64`; the `raw_number`
+    /// feature adds `RawNumber` to keep its text instead, outside of `Value`.
+    ///
+    /// Returns `None` if `s` is not a valid JSON number or is out of the `f64` range.
+    pub fn from_decimal_str(s: &str) -> Option<Number> {
+        if !is_json_number(s) {
+            return None;
+        }
+
+        if let Ok(n) = s.parse::<u64>() {
+            return Some(N::PosInt(n).into());
+        } else if let Ok(n) = s.parse::<i64>()
+            && n < 0
+        {
+            return Some(N::NegInt(n).into());
+        }
+
+        s.parse::<f64>()
+            .ok()
+            .filter(|n| n.is_finite())
+            .map(|n| N::Float(n).into())
+    }
+
     /// If the `Number` is an integer, represent it as i64 if possible. Returns
     /// None otherwise.
     pub fn as_u64(&self) -> Option<u64> {
@@ -121,6 +206,61 @@ impl PartialEq for N {
 // Implementing Eq is fine since any float values are always finite.
 impl Eq for N {}
 
+impl N {
+    fn rank(&self) -> u8 {
+        match self {
+            N::PosInt(_) => 0,
+            N::NegInt(_) => 1,
+            N::Float(_) => 2,
+        }
+    }
+
+    fn as_i128(&self) -> i128 {
+        match *self {
+            N::PosInt(v) => v as i128,
+            N::NegInt(v) => v as i128,
+            N::Float(v) => v as i128,
+        }
+    }
+}
+
+// Numbers are ordered by their mathematical value. Numbers with the same value
+// but a different representation are ordered by representation to keep `Ord`
+// consistent with `Eq`.
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.n, other.n) {
+            (N::Float(a), N::Float(b)) => a.partial_cmp(&b).unwrap_or_else(|| a.total_cmp(&b)),
+            (N::Float(a), b) => cmp_int_float(b.as_i128(), a).reverse(),
+            (a, N::Float(b)) => cmp_int_float(a.as_i128(), b),
+            (a, b) => a.as_i128().cmp(&b.as_i128()),
+        }
+        .then_with(|| self.n.rank().cmp(&other.n.rank()))
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn cmp_int_float(int: i128, float: f64) -> Ordering {
+    if float.is_nan() || float >= 18446744073709551616.0 {
+        Ordering::Less
+    } else if float < -9223372036854775808.0 {
+        Ordering::Greater
+    } else {
+        // The cast truncates towards zero and is exact within the range checked above
+        let trunc = float as i128;
+        int.cmp(&trunc).then_with(|| {
+            0.0f64
+                .partial_cmp(&(float - trunc as f64))
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
 impl Hash for N {
     fn hash<H: Hasher>(&self, h: &mut H) {
         match *self {
@@ -139,6 +279,48 @@ impl Hash for N {
         }
     }
 }
+
+// Floats are formatted by serde_json so the output is the shortest representation
+// that round-trips, identical to what the serializer emits.
+impl Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.n {
+            N::PosInt(n) => Display::fmt(&n, f),
+            N::NegInt(n) => Display::fmt(&n, f),
+            N::Float(n) => match serde_json::Number::from_f64(n) {
+                Some(n) => Display::fmt(&n, f),
+                None => f.write_str("null"),
+            },
+        }
+    }
+}
+
+impl PartialEq<u64> for Number {
+    fn eq(&self, other: &u64) -> bool {
+        match self.n {
+            N::PosInt(v) => v == *other,
+            N::NegInt(v) => u64::try_from(v) == Ok(*other),
+            N::Float(v) => v == *other as f64 && v as u64 == *other,
+        }
+    }
+}
+
+impl PartialEq<i64> for Number {
+    fn eq(&self, other: &i64) -> bool {
+        match self.n {
+            N::PosInt(v) => i64::try_from(v) == Ok(*other),
+            N::NegInt(v) => v == *other,
+            N::Float(v) => v == *other as f64 && v as i64 == *other,
+        }
+    }
+}
+
+impl PartialEq<f64> for Number {
+    fn eq(&self, other: &f64) -> bool {
+        self.as_f64() == Some(*other)
+    }
+}
+
 impl From<u64> for Number {
     fn from(val: u64) -> Self {
         Self { n: N::PosInt(val) }
@@ -191,6 +373,12 @@ impl From<i32> for Number {
 
 impl From<Number> for serde_json::value::Number {
     fn from(num: Number) -> Self {
+        (&num).into()
+    }
+}
+
+impl From<&Number> for serde_json::value::Number {
+    fn from(num: &Number) -> Self {
         match num.n {
             N::PosInt(n) => n.into(),
             N::NegInt(n) => n.into(),
@@ -198,3 +386,119 @@ impl From<Number> for serde_json::value::Number {
         }
     }
 }
+
+// Matches the JSON number grammar: -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+fn is_json_number(s: &str) -> bool {
+    let mut bytes = s.as_bytes();
+    let digits = |bytes: &mut &[u8]| {
+        let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+        *bytes = &bytes[len..];
+        len
+    };
+
+    if let [b'-', rest @ ..] = bytes {
+        bytes = rest;
+    }
+    match bytes {
+        [b'0', rest @ ..] => bytes = rest,
+        [b'1'..=b'9', ..] => {
+            digits(&mut bytes);
+        }
+        _ => return false,
+    }
+    if let [b'.', rest @ ..] = bytes {
+        bytes = rest;
+        if digits(&mut bytes) == 0 {
+            return false;
+        }
+    }
+    if let [b'e' | b'E', rest @ ..] = bytes {
+        bytes = rest;
+        if let [b'+' | b'-', rest @ ..] = bytes {
+            bytes = rest;
+        }
+        if digits(&mut bytes) == 0 {
+            return false;
+        }
+    }
+    bytes.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Number;
+
+    #[test]
+    fn number_display() {
+        assert_eq!(Number::from(1u64).to_string(), "1");
+        assert_eq!(Number::from(-1i64).to_string(), "-1");
+        assert_eq!(Number::from(1.5f64).to_string(), "1.5");
+        assert_eq!(Number::from(1e100f64).to_string(), "1e+100");
+        assert_eq!(
+            Number::from(0.1f64 + 0.2f64).to_string(),
+            "0.30000000000000004"
+        );
+        assert_eq!(Number::from(f64::NAN).to_string(), "null");
+    }
+
+    #[test]
+    fn number_from_decimal_str() {
+        for (input, expected) in [
+            ("0", Number::from(0u64)),
+            ("18446744073709551615", Number::from(u64::MAX)),
+            ("-9223372036854775808", Number::from(i64::MIN)),
+            ("-1", Number::from(-1i64)),
+            ("1.5", Number::from(1.5f64)),
+            ("-0.25", Number::from(-0.25f64)),
+            ("1e+100", Number::from(1e100f64)),
+        ] {
+            assert!(Number::from_decimal_str(input) == Some(expected), "{input}");
+        }
+
+        for input in [
+            "", "-", "+1", "01", "1.", ".5", "1e", "1e+", "0x10", " 1", "1 ", "NaN", "1e400",
+        ] {
+            assert!(Number::from_decimal_str(input).is_none(), "{input}");
+        }
+
+        for input in [
+            "-0",
+            "1.50",
+            "1E2",
+            "18446744073709551616",
+            "0.1000000000000000055511151231257827",
+        ] {
+            let number = Number::from_decimal_str(input).unwrap();
+            assert_eq!(number.as_u64(), None, "{input}");
+            assert_eq!(number.as_f64(), input.parse().ok(), "{input}");
+            assert!(number.is_f64());
+        }
+    }
+
+    #[cfg(feature = "raw_number")]
+    #[test]
+    fn raw_number() {
+        use super::RawNumber;
+
+        for input in [
+            "-0",
+            "1.50",
+            "1E2",
+            "18446744073709551616",
+            "0.1000000000000000055511151231257827",
+        ] {
+            let raw = RawNumber::from_decimal_str(input).unwrap();
+            assert_eq!(raw.as_str(), input);
+            assert_eq!(raw.to_string(), input);
+            assert_eq!(serde_json::to_string(&raw).unwrap(), input);
+            assert!(!raw.is_exact(), "{input}");
+            assert!(raw.to_number() == Number::from_decimal_str(input).unwrap());
+        }
+
+        let raw = RawNumber::from_decimal_str("42").unwrap();
+        assert!(raw.is_exact());
+        assert_eq!(raw.to_number().as_u64(), Some(42));
+        assert!(RawNumber::from_decimal_str("1e400").is_none());
+        assert!(RawNumber::from_decimal_str("01").is_none());
+    }
+}
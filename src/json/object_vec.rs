@@ -7,12 +7,18 @@
 #![allow(clippy::useless_conversion)]
 #![allow(clippy::useless_asref)]
 
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
+use core::{cmp::Ordering, fmt, ops};
+
 use crate::{
     Value,
     json::key::Key,
     json::value::{Element, Property},
 };
 
+/// A key/value entry of an [`ObjectAsVec`].
+pub type ObjectEntry<'ctx, P, E> = (Key<'ctx, P>, Value<'ctx, P, E>);
+
 /// Represents a JSON key/value type.
 ///
 /// For performance reasons we use a Vec instead of a Hashmap.
@@ -21,7 +27,13 @@ use crate::{
 /// The ObjectAsVec struct is a wrapper around a Vec of (&str, Value) pairs.
 /// It provides methods to make it easy to migrate from serde_json::Value::Object or
 /// serde_json::Map.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+///
+/// Entries are kept in insertion order: `insert` replaces the value of an existing key in
+/// place, while new keys and `insert_unchecked` append to the end. Since `insert_unchecked`
+/// does not check for duplicates, the same key may appear more than once; lookups return the
+/// first occurrence. `remove` moves the last entry into the place of the removed one, use
+/// `shift_remove` to keep the order of the remaining entries.
+#[derive(Default, Clone, PartialEq, Eq, Hash)]
 pub struct ObjectAsVec<'ctx, P: Property, E: Element>(
     pub(crate) Vec<(Key<'ctx, P>, Value<'ctx, P, E>)>,
 );
@@ -51,6 +63,24 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         Self(Vec::with_capacity(capacity))
     }
 
+    /// Reserves capacity for at least `additional` more entries.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Returns the number of entries the object can hold without reallocating.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Shrinks the capacity of the object as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
     /// Access to the underlying Vec.
     ///
     /// # Note
@@ -88,7 +118,39 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
     #[inline]
     pub fn get_ignore_case(&self, key: &str) -> Option<&Value<'ctx, P, E>> {
         self.0.iter().find_map(|(k, v)| {
-            if k.to_string().eq_ignore_ascii_case(key) {
+            if key_eq_ignore_case(k, key) {
+                Some(v)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the key-value pair whose key matches the supplied key, ignoring ASCII case.
+    ///
+    /// ## Performance
+    /// As this is backed by a Vec, this searches linearly through the Vec as may be much more
+    /// expensive than a `Hashmap` for larger Objects.
+    #[inline]
+    pub fn get_key_value_ignore_case(
+        &self,
+        key: &str,
+    ) -> Option<(&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        self.0.iter().find_map(|(k, v)| {
+            if key_eq_ignore_case(k, key) {
+                Some((k, v))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns a mutable reference to the value whose key matches the supplied key, ignoring
+    /// ASCII case.
+    #[inline]
+    pub fn get_ignore_case_mut(&mut self, key: &str) -> Option<&mut Value<'ctx, P, E>> {
+        self.0.iter_mut().find_map(|(k, v)| {
+            if key_eq_ignore_case(k, key) {
                 Some(v)
             } else {
                 None
@@ -108,6 +170,51 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
             .find_map(move |(k, v)| if k == key { Some(v) } else { None })
     }
 
+    /// Returns mutable references to the values of several keys at once, returning `None` for
+    /// keys that are not present.
+    ///
+    /// ## Panics
+    /// Panics if the same key is requested more than once.
+    ///
+    /// ## Performance
+    /// As this is backed by a Vec, this searches linearly through the Vec as may be much more
+    /// expensive than a `Hashmap` for larger Objects.
+    pub fn get_disjoint_mut<const N: usize>(
+        &mut self,
+        keys: [&Key<'_, P>; N],
+    ) -> [Option<&mut Value<'ctx, P, E>>; N] {
+        match self.get_many_mut(&keys).try_into() {
+            Ok(values) => values,
+            Err(_) => unreachable!("get_many_mut returns one value per key"),
+        }
+    }
+
+    /// Returns mutable references to the values of a slice of keys, in the same order as
+    /// `keys`, with `None` for keys that are not present. The object is scanned only once
+    /// regardless of the number of keys.
+    ///
+    /// ## Panics
+    /// Panics if the same key is requested more than once, as with
+    /// [`ObjectAsVec::get_disjoint_mut`].
+    pub fn get_many_mut(&mut self, keys: &[&Key<'_, P>]) -> Vec<Option<&mut Value<'ctx, P, E>>> {
+        for (pos, key) in keys.iter().enumerate() {
+            if keys[pos + 1..].contains(key) {
+                panic!("duplicate key {:?} requested", key.to_string());
+            }
+        }
+
+        let mut values = Vec::with_capacity(keys.len());
+        values.resize_with(keys.len(), || None);
+        for (k, v) in self.0.iter_mut() {
+            if let Some(pos) = keys.iter().position(|key| *key == &*k)
+                && values[pos].is_none()
+            {
+                values[pos] = Some(v);
+            }
+        }
+        values
+    }
+
     /// Returns the key-value pair corresponding to the supplied key.
     ///
     /// ## Performance
@@ -133,6 +240,38 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         self.0.iter_mut().map(|(k, v)| (k, v))
     }
 
+    /// Returns references to the entries sorted by their key string, leaving the object
+    /// untouched. Entries with the same key keep their insertion order.
+    pub fn entries_sorted(&self) -> Vec<(&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_by_cached_key(|&(k, _)| k.to_string());
+        entries
+    }
+
+    /// Returns the first entry in insertion order.
+    #[inline]
+    pub fn first(&self) -> Option<(&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        self.0.first().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the last entry in insertion order.
+    #[inline]
+    pub fn last(&self) -> Option<(&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        self.0.last().map(|(k, v)| (k, v))
+    }
+
+    /// Returns the first entry in insertion order with a mutable reference to its value.
+    #[inline]
+    pub fn first_mut(&mut self) -> Option<(&Key<'ctx, P>, &mut Value<'ctx, P, E>)> {
+        self.0.first_mut().map(|(k, v)| (&*k, v))
+    }
+
+    /// Returns the last entry in insertion order with a mutable reference to its value.
+    #[inline]
+    pub fn last_mut(&mut self) -> Option<(&Key<'ctx, P>, &mut Value<'ctx, P, E>)> {
+        self.0.last_mut().map(|(k, v)| (&*k, v))
+    }
+
     /// Returns the number of elements in the object
     #[inline]
     pub fn len(&self) -> usize {
@@ -151,12 +290,39 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         self.0.iter().map(|(k, _)| k)
     }
 
+    /// An iterator visiting the string form of all keys in insertion order.
+    pub fn key_strings(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        self.0.iter().map(|(k, _)| k.to_string())
+    }
+
+    /// Returns the keys that are not in `known`, in insertion order. Keys are compared with
+    /// `Key` equality, so a string key matches the property with the same name.
+    pub fn unknown_keys(&self, known: &[Key<'_, P>]) -> Vec<Cow<'_, str>> {
+        self.0
+            .iter()
+            .filter(|(k, _)| !known.iter().any(|known| known == k))
+            .map(|(k, _)| k.to_string())
+            .collect()
+    }
+
     /// An iterator visiting all values
     #[inline]
     pub fn values(&self) -> impl Iterator<Item = &Value<'ctx, P, E>> {
         self.0.iter().map(|(_, v)| v)
     }
 
+    /// Creates a consuming iterator visiting all keys in insertion order
+    #[inline]
+    pub fn into_keys(self) -> impl Iterator<Item = Key<'ctx, P>> {
+        self.0.into_iter().map(|(k, _)| k)
+    }
+
+    /// Creates a consuming iterator visiting all values in insertion order
+    #[inline]
+    pub fn into_values(self) -> impl Iterator<Item = Value<'ctx, P, E>> {
+        self.0.into_iter().map(|(_, v)| v)
+    }
+
     /// Returns true if the object contains a value for the specified key.
     ///
     /// ## Performance
@@ -172,11 +338,81 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         self.0.iter().any(|(k, v)| k == key && v == value)
     }
 
+    /// Returns true if any key in the object maps to the supplied value.
+    ///
+    /// ## Performance
+    /// This is a linear scan over all the values in the object.
+    #[inline]
+    pub fn contains_value(&self, value: &Value<'ctx, P, E>) -> bool {
+        self.0.iter().any(|(_, v)| v == value)
+    }
+
+    /// Returns the first key that maps to the supplied value.
+    ///
+    /// ## Performance
+    /// This is a linear scan over all the values in the object.
+    #[inline]
+    pub fn find_key_by_value(&self, value: &Value<'ctx, P, E>) -> Option<&Key<'ctx, P>> {
+        self.0
+            .iter()
+            .find_map(|(k, v)| if v == value { Some(k) } else { None })
+    }
+
     #[inline]
     pub fn contains_any_key(&self, keys: &[Key<'ctx, P>]) -> bool {
         self.0.iter().any(|(k, _)| keys.contains(k))
     }
 
+    /// Returns the position of the key in the object.
+    #[inline]
+    pub fn position(&self, key: &Key<'_, P>) -> Option<usize> {
+        self.0.iter().position(|(k, _)| k == key)
+    }
+
+    /// Swaps the entries at positions `i` and `j`.
+    ///
+    /// ## Panics
+    /// Panics if `i` or `j` are out of bounds.
+    #[inline]
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.0.swap(i, j);
+    }
+
+    /// Moves the entry with the supplied key to the front of the object, preserving the
+    /// relative order of the other entries. Returns false if the key is not present.
+    pub fn move_key_to_front(&mut self, key: &Key<'_, P>) -> bool {
+        if let Some(pos) = self.position(key) {
+            self.0[..=pos].rotate_right(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves the entry with the supplied key to the back of the object, preserving the
+    /// relative order of the other entries. Returns false if the key is not present.
+    pub fn move_key_to_back(&mut self, key: &Key<'_, P>) -> bool {
+        if let Some(pos) = self.position(key) {
+            self.0[pos..].rotate_left(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Sorts the entries by key using the supplied comparator. The sort is stable, so entries
+    /// with keys comparing as equal keep their relative order.
+    pub fn sort_keys_by<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Key<'ctx, P>, &Key<'ctx, P>) -> Ordering,
+    {
+        self.0.sort_by(|(a, _), (b, _)| f(a, b));
+    }
+
+    /// Removes the first occurrence of a key, returning its value.
+    ///
+    /// The last entry is moved into the position of the removed one, so this does not preserve
+    /// insertion order. Use [`ObjectAsVec::shift_remove`] when the order matters.
     pub fn remove(&mut self, key: &Key<'ctx, P>) -> Option<Value<'ctx, P, E>> {
         if let Some(pos) = self.0.iter().position(|(k, _)| k == key) {
             Some(self.0.swap_remove(pos).1)
@@ -185,6 +421,69 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         }
     }
 
+    /// Removes the first occurrence of a key, returning its value. The entries after it are
+    /// shifted down, preserving insertion order at a cost linear in their number.
+    pub fn shift_remove(&mut self, key: &Key<'ctx, P>) -> Option<Value<'ctx, P, E>> {
+        if let Some(pos) = self.0.iter().position(|(k, _)| k == key) {
+            Some(self.0.remove(pos).1)
+        } else {
+            None
+        }
+    }
+
+    /// Retains only the key-value pairs for which the predicate returns `true`.
+    /// The relative order of the remaining entries is preserved.
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Key<'ctx, P>, &mut Value<'ctx, P, E>) -> bool,
+    {
+        self.0.retain_mut(|(k, v)| f(k, v));
+    }
+
+    /// Consumes the object and rebuilds it from the entries returned by `f`, dropping those for
+    /// which it returns `None`. Both keys and values can be replaced; the order of the entries
+    /// is preserved, and keys are not checked for duplicates, as with `insert_unchecked`.
+    pub fn filter_map_values<F>(self, mut f: F) -> ObjectAsVec<'ctx, P, E>
+    where
+        F: FnMut(Key<'ctx, P>, Value<'ctx, P, E>) -> Option<ObjectEntry<'ctx, P, E>>,
+    {
+        ObjectAsVec(self.0.into_iter().filter_map(|(k, v)| f(k, v)).collect())
+    }
+
+    /// Removes the entries for which the predicate returns `true` and yields them, preserving
+    /// the relative order of both the extracted and the retained entries.
+    ///
+    /// Like `Vec::extract_if`, entries are only visited as the iterator is advanced; dropping
+    /// it early keeps the remaining entries in the object.
+    pub fn extract_if<F>(
+        &mut self,
+        mut f: F,
+    ) -> impl Iterator<Item = (Key<'ctx, P>, Value<'ctx, P, E>)>
+    where
+        F: FnMut(&Key<'ctx, P>, &mut Value<'ctx, P, E>) -> bool,
+    {
+        self.0.extract_if(.., move |(k, v)| f(k, v))
+    }
+
+    /// Splits the object into the entries for which the predicate returns `true` and those for
+    /// which it returns `false`, preserving the relative order in both.
+    pub fn partition<F>(self, mut f: F) -> (Self, Self)
+    where
+        F: FnMut(&Key<'ctx, P>, &Value<'ctx, P, E>) -> bool,
+    {
+        let mut matched = ObjectAsVec::new();
+        let mut rest = ObjectAsVec::new();
+        for (key, value) in self.into_vec() {
+            if f(&key, &value) {
+                matched.0.push((key, value));
+            } else {
+                rest.0.push((key, value));
+            }
+        }
+        (matched, rest)
+    }
+
     /// Inserts a key-value pair into the object.
     /// If the object did not have this key present, `None` is returned.
     /// If the object did have this key present, the value is updated, and the old value is
@@ -202,7 +501,7 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         let key = key.into();
         for (k, v) in &mut self.0 {
             if k == &key {
-                return Some(std::mem::replace(v, value.into()));
+                return Some(core::mem::replace(v, value.into()));
             }
         }
         // If the key is not found, push the new key-value pair to the end of the Vec
@@ -271,6 +570,69 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
         self.0.extend(iter);
     }
 
+    /// Inserts all key-value pairs from the iterator, replacing the value of any key that is
+    /// already present instead of appending a duplicate. Returns the number of values that were
+    /// replaced.
+    ///
+    /// ## Performance
+    /// This operation is O(n·m) as every incoming key is compared against the existing ones. For
+    /// bulk loads it may be cheaper to use `extend` followed by [`ObjectAsVec::dedup_keys`].
+    pub fn extend_unique<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator<Item = (Key<'ctx, P>, Value<'ctx, P, E>)>,
+    {
+        let mut replaced = 0;
+        for (key, value) in iter {
+            if self.insert(key, value).is_some() {
+                replaced += 1;
+            }
+        }
+        replaced
+    }
+
+    /// Moves all entries of `other` into `self`, leaving `other` empty. Keys already present in
+    /// `self` have their value replaced (last wins); duplicate keys within `other` are collapsed
+    /// in the same way, so `self` never gains duplicates from this call.
+    pub fn append(&mut self, other: &mut ObjectAsVec<'ctx, P, E>) {
+        self.0.reserve(other.0.len());
+        for (key, value) in other.0.drain(..) {
+            self.insert(key, value);
+        }
+    }
+
+    /// Moves all entries of `other` into `self`, calling `resolve(key, existing, incoming)` for
+    /// keys present in both and storing its result in place. Keys only in `other` are appended
+    /// in their original order.
+    pub fn merge_with<F>(&mut self, other: ObjectAsVec<'ctx, P, E>, mut resolve: F)
+    where
+        F: FnMut(&Key<'ctx, P>, Value<'ctx, P, E>, Value<'ctx, P, E>) -> Value<'ctx, P, E>,
+    {
+        self.0.reserve(other.0.len());
+        for (key, incoming) in other.0 {
+            if let Some(pos) = self.position(&key) {
+                let existing = core::mem::take(&mut self.0[pos].1);
+                self.0[pos].1 = resolve(&key, existing, incoming);
+            } else {
+                self.0.push((key, incoming));
+            }
+        }
+    }
+
+    /// Removes duplicate keys from the object. The first occurrence of a key keeps its position
+    /// and receives the value of the last occurrence, matching the result of repeated `insert`
+    /// calls.
+    pub fn dedup_keys(&mut self) {
+        let mut deduped: Vec<(Key<'ctx, P>, Value<'ctx, P, E>)> = Vec::with_capacity(self.0.len());
+        for (key, value) in self.0.drain(..) {
+            if let Some((_, v)) = deduped.iter_mut().find(|(k, _)| k == &key) {
+                *v = value;
+            } else {
+                deduped.push((key, value));
+            }
+        }
+        self.0 = deduped;
+    }
+
     /// Inserts a key-value pair into the object and returns the mutable reference of the inserted
     /// value.
     ///
@@ -294,6 +656,95 @@ impl<'ctx, P: Property, E: Element> ObjectAsVec<'ctx, P, E> {
     }
 }
 
+/// Formats the object as a map from key strings to values, in insertion order.
+impl<P: Property, E: Element> fmt::Debug for ObjectAsVec<'_, P, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(key, value)| (key.to_string(), value)))
+            .finish()
+    }
+}
+
+/// Objects are ordered by their entries sorted by key, falling back to insertion order
+/// so that `Ord` stays consistent with `Eq`. Each comparison collects and sorts the entries
+/// of both objects into two temporary Vecs, so sorting many large objects is costly.
+impl<P: Property, E: Element> Ord for ObjectAsVec<'_, P, E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        sorted_entries(self)
+            .cmp(&sorted_entries(other))
+            .then_with(|| {
+                self.0
+                    .iter()
+                    .map(|(k, _)| k.to_string())
+                    .cmp(other.0.iter().map(|(k, _)| k.to_string()))
+            })
+    }
+}
+
+impl<P: Property, E: Element> PartialOrd for ObjectAsVec<'_, P, E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Indexing panics if the key is not present, use [`ObjectAsVec::get`] for a non-panicking
+/// lookup.
+impl<'ctx, P: Property, E: Element> ops::Index<&Key<'_, P>> for ObjectAsVec<'ctx, P, E> {
+    type Output = Value<'ctx, P, E>;
+
+    fn index(&self, key: &Key<'_, P>) -> &Self::Output {
+        self.get(key)
+            .unwrap_or_else(|| panic!("key {:?} not found in object", key.to_string()))
+    }
+}
+
+/// Indexing panics if the key is not present, use [`ObjectAsVec::get`] for a non-panicking
+/// lookup.
+impl<'ctx, P: Property, E: Element> ops::Index<&str> for ObjectAsVec<'ctx, P, E> {
+    type Output = Value<'ctx, P, E>;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.0
+            .iter()
+            .find_map(|(k, v)| if *k == key { Some(v) } else { None })
+            .unwrap_or_else(|| panic!("key {key:?} not found in object"))
+    }
+}
+
+/// Indexing panics if the key is not present, as new keys cannot be inserted through
+/// `IndexMut`. Use [`ObjectAsVec::get_mut`] or [`ObjectAsVec::insert`] instead.
+impl<P: Property, E: Element> ops::IndexMut<&Key<'_, P>> for ObjectAsVec<'_, P, E> {
+    fn index_mut(&mut self, key: &Key<'_, P>) -> &mut Self::Output {
+        self.0
+            .iter_mut()
+            .find_map(|(k, v)| if key == &*k { Some(v) } else { None })
+            .unwrap_or_else(|| panic!("key {:?} not found in object", key.to_string()))
+    }
+}
+
+fn sorted_entries<'x, 'ctx, P: Property, E: Element>(
+    obj: &'x ObjectAsVec<'ctx, P, E>,
+) -> Vec<(Cow<'x, str>, &'x Value<'ctx, P, E>)> {
+    let mut entries = obj
+        .0
+        .iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect::<Vec<_>>();
+    // Stable, so duplicate keys keep their insertion order
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+#[inline]
+fn key_eq_ignore_case<P: Property>(k: &Key<'_, P>, key: &str) -> bool {
+    match k {
+        Key::Borrowed(k) => k.eq_ignore_ascii_case(key),
+        Key::Owned(k) => k.eq_ignore_ascii_case(key),
+        Key::Shared(k) => k.eq_ignore_ascii_case(key),
+        Key::Property(k) => k.to_cow().eq_ignore_ascii_case(key),
+    }
+}
+
 impl<'ctx, P: Property, E: Element<Property = P>> ObjectAsVec<'ctx, P, E> {
     pub fn into_expanded_boolean_set(self) -> impl Iterator<Item = Key<'ctx, P>> {
         self.into_vec()
@@ -320,3 +771,639 @@ impl<'ctx, P: Property, E: Element> From<&ObjectAsVec<'ctx, P, E>>
             .collect()
     }
 }
+
+impl<P: Property, E: Element> From<serde_json::Map<String, serde_json::Value>>
+    for ObjectAsVec<'static, P, E>
+{
+    fn from(val: serde_json::Map<String, serde_json::Value>) -> Self {
+        val.into_iter()
+            .map(|(key, val)| {
+                let key = match P::try_parse(None, &key) {
+                    Some(prop) => Key::Property(prop),
+                    None => Key::Owned(key),
+                };
+                (key, val.into())
+            })
+            .collect()
+    }
+}
+
+impl<'ctx, P: Property, E: Element> From<&'ctx serde_json::Map<String, serde_json::Value>>
+    for ObjectAsVec<'ctx, P, E>
+{
+    fn from(val: &'ctx serde_json::Map<String, serde_json::Value>) -> Self {
+        val.iter()
+            .map(|(key, val)| (Key::from(key.as_str()), val.into()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Key, Null, ObjectAsVec, Property, Value};
+    use std::borrow::Cow;
+    use std::cmp::Ordering;
+
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum TestProp {
+        Id,
+        Name,
+    }
+
+    impl Property for TestProp {
+        fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+            match value {
+                "id" => Some(TestProp::Id),
+                "name" => Some(TestProp::Name),
+                _ => None,
+            }
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            match self {
+                TestProp::Id => "id".into(),
+                TestProp::Name => "name".into(),
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let obj: ObjectAsVec<'_, TestProp, Null> = ObjectAsVec::new()
+            .with_key_value(Key::Property(TestProp::Id), 1u64)
+            .with_key_value(Key::Borrowed("x-custom"), true)
+            .with_key_value(Key::Owned("name".to_string()), "Inbox")
+            .with_key_value(Key::Borrowed("sortOrder"), 2u64);
+
+        assert_eq!(
+            obj.key_strings().collect::<Vec<_>>(),
+            vec!["id", "x-custom", "name", "sortOrder"]
+        );
+        assert_eq!(
+            obj.unknown_keys(&[Key::Property(TestProp::Id), Key::Property(TestProp::Name)]),
+            vec!["x-custom", "sortOrder"]
+        );
+        assert_eq!(
+            obj.unknown_keys(&[Key::Borrowed("id"), Key::Borrowed("sortOrder")]),
+            vec!["x-custom", "name"]
+        );
+        assert_eq!(obj.unknown_keys(&[]).len(), 4);
+    }
+
+    #[test]
+    fn get_ignore_case() {
+        let mut obj: ObjectAsVec<'_, TestProp, Null> = ObjectAsVec::new()
+            .with_key_value(Key::Property(TestProp::Name), "Inbox")
+            .with_key_value(Key::Borrowed("sortOrder"), 2u64)
+            .with_key_value(Key::Owned("X-Custom".to_string()), true);
+
+        assert_eq!(
+            obj.get_ignore_case("NAME"),
+            Some(&Value::Str("Inbox".into()))
+        );
+        assert_eq!(
+            obj.get_ignore_case("SortOrder"),
+            Some(&Value::Number(2u64.into()))
+        );
+        assert_eq!(obj.get_ignore_case("x-custom"), Some(&Value::Bool(true)));
+        assert_eq!(obj.get_ignore_case("x-other"), None);
+
+        let (key, value) = obj.get_key_value_ignore_case("SORTORDER").unwrap();
+        assert_eq!(key, &Key::Borrowed("sortOrder"));
+        assert_eq!(value, &Value::Number(2u64.into()));
+        let (key, _) = obj.get_key_value_ignore_case("Name").unwrap();
+        assert_eq!(key, &Key::Property(TestProp::Name));
+        assert!(obj.get_key_value_ignore_case("names").is_none());
+
+        *obj.get_ignore_case_mut("x-CUSTOM").unwrap() = Value::Bool(false);
+        *obj.get_ignore_case_mut("nAmE").unwrap() = Value::Str("Sent".into());
+        assert!(obj.get_ignore_case_mut("id").is_none());
+        assert_eq!(
+            obj.get(&Key::Owned("X-Custom".to_string())),
+            Some(&Value::Bool(false))
+        );
+        assert_eq!(
+            obj.get(&Key::Property(TestProp::Name)),
+            Some(&Value::Str("Sent".into()))
+        );
+    }
+
+    #[test]
+    fn extend_unique() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64);
+
+        let replaced = obj.extend_unique([
+            (Key::Borrowed("b"), Value::Number(3u64.into())),
+            (Key::Borrowed("c"), Value::Number(4u64.into())),
+        ]);
+
+        assert_eq!(replaced, 1);
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", 1u64)
+                .with_key_value("b", 3u64)
+                .with_key_value("c", 4u64)
+        );
+    }
+
+    #[test]
+    fn append() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64);
+        let mut other = ObjectAsVec::from(vec![
+            (Key::Borrowed("b"), Value::Number(3u64.into())),
+            (Key::Borrowed("c"), Value::Number(4u64.into())),
+            (Key::Borrowed("c"), Value::Number(5u64.into())),
+        ]);
+
+        obj.append(&mut other);
+
+        assert!(other.is_empty());
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", 1u64)
+                .with_key_value("b", 3u64)
+                .with_key_value("c", 5u64)
+        );
+    }
+
+    #[test]
+    fn first_last() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new();
+        assert!(obj.first().is_none() && obj.last().is_none());
+
+        obj.insert(Key::Borrowed("a"), Value::Number(1u64.into()));
+        obj.insert_unchecked(Key::Borrowed("b"), Value::Number(2u64.into()));
+        obj.insert(Key::Borrowed("c"), Value::Number(3u64.into()));
+        obj.insert(Key::Borrowed("a"), Value::Number(4u64.into()));
+
+        assert_eq!(
+            obj.first(),
+            Some((&Key::Borrowed("a"), &Value::Number(4u64.into())))
+        );
+        assert_eq!(
+            obj.last(),
+            Some((&Key::Borrowed("c"), &Value::Number(3u64.into())))
+        );
+
+        *obj.first_mut().unwrap().1 = Value::Null;
+        *obj.last_mut().unwrap().1 = Value::Bool(true);
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", Value::Null)
+                .with_key_value("b", 2u64)
+                .with_key_value("c", true)
+        );
+    }
+
+    #[test]
+    fn index() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64);
+
+        assert_eq!(obj["a"], Value::Number(1u64.into()));
+        assert_eq!(
+            obj[&Key::Owned("b".to_string())],
+            Value::Number(2u64.into())
+        );
+
+        obj[&Key::Borrowed("b")] = Value::Bool(true);
+        assert_eq!(obj["b"], Value::Bool(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "key \"c\" not found in object")]
+    fn index_missing_key() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new().with_key_value("a", 1u64);
+        obj[&Key::Borrowed("c")] = Value::Null;
+    }
+
+    #[test]
+    fn into_keys_values() {
+        let obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", true);
+
+        assert_eq!(
+            obj.clone().into_keys().collect::<Vec<_>>(),
+            vec![Key::Borrowed("a"), Key::Borrowed("b")]
+        );
+        assert_eq!(
+            obj.into_values().collect::<Vec<_>>(),
+            vec![Value::Number(1u64.into()), Value::Bool(true)]
+        );
+    }
+
+    #[test]
+    fn extract_if() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("x-a", 1u64)
+            .with_key_value("id", 2u64)
+            .with_key_value("x-b", 3u64)
+            .with_key_value("name", 4u64)
+            .with_key_value("x-c", 5u64);
+
+        let extracted = obj
+            .extract_if(|key, _| key.to_string().starts_with("x-"))
+            .collect::<ObjectAsVec<'_, Null, Null>>();
+        assert_eq!(
+            extracted,
+            ObjectAsVec::new()
+                .with_key_value("x-a", 1u64)
+                .with_key_value("x-b", 3u64)
+                .with_key_value("x-c", 5u64)
+        );
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("id", 2u64)
+                .with_key_value("name", 4u64)
+        );
+    }
+
+    #[test]
+    fn partition() {
+        let obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("id", 1u64)
+            .with_key_value("x-custom", true)
+            .with_key_value("subject", "hello")
+            .with_key_value("x-other", Value::Null);
+
+        let (known, unknown) =
+            obj.partition(|key, _| matches!(key.to_string().as_ref(), "id" | "subject"));
+        assert_eq!(
+            known,
+            ObjectAsVec::new()
+                .with_key_value("id", 1u64)
+                .with_key_value("subject", "hello")
+        );
+        assert_eq!(
+            unknown,
+            ObjectAsVec::new()
+                .with_key_value("x-custom", true)
+                .with_key_value("x-other", Value::Null)
+        );
+    }
+
+    #[test]
+    fn serde_json_map_round_trip() {
+        let serde_json::Value::Object(map) = serde_json::json!({
+            "id": 1,
+            "name": "Inbox",
+            "role": {"sort": -2.5, "ids": [null, true, "x"]},
+        }) else {
+            unreachable!()
+        };
+
+        let obj = ObjectAsVec::<'_, Null, Null>::from(&map);
+        assert_eq!(
+            obj.get(&Key::Borrowed("name")),
+            Some(&Value::Str("Inbox".into()))
+        );
+        assert_eq!(serde_json::Map::from(&obj), map);
+
+        let obj = ObjectAsVec::<'static, Null, Null>::from(map.clone());
+        assert_eq!(obj, ObjectAsVec::from(&map));
+        assert_eq!(serde_json::Map::from(obj), map);
+    }
+
+    #[test]
+    fn entries_sorted() {
+        let obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("subject", "hello")
+            .with_key_value("b", 2u64)
+            .with_key_value("a", 1u64)
+            .with_key_value("Z", true);
+
+        assert_eq!(
+            obj.entries_sorted()
+                .into_iter()
+                .map(|(k, v)| (k.to_string().into_owned(), v.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Z".to_string(), Value::Bool(true)),
+                ("a".to_string(), Value::Number(1u64.into())),
+                ("b".to_string(), Value::Number(2u64.into())),
+                ("subject".to_string(), Value::Str("hello".into())),
+            ]
+        );
+        assert_eq!(obj.keys().next(), Some(&Key::Borrowed("subject")));
+        assert!(
+            ObjectAsVec::<'_, Null, Null>::new()
+                .entries_sorted()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn get_disjoint_mut() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64);
+
+        let [a, b, c] =
+            obj.get_disjoint_mut([&Key::Borrowed("a"), &Key::Borrowed("b"), &"c".into()]);
+        std::mem::swap(a.unwrap(), b.unwrap());
+        assert!(c.is_none());
+
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", 2u64)
+                .with_key_value("b", 1u64)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key \"a\" requested")]
+    fn get_disjoint_mut_duplicate_key() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new().with_key_value("a", 1u64);
+        obj.get_disjoint_mut([&Key::Borrowed("a"), &Key::Owned("a".to_string())]);
+    }
+
+    #[test]
+    fn ord_duplicate_keys() {
+        let mut a: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new();
+        a.insert_unchecked("a", 1u64);
+        a.insert_unchecked("a", 2u64);
+        let mut b: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new();
+        b.insert_unchecked("a", 2u64);
+        b.insert_unchecked("a", 1u64);
+
+        assert_ne!(a, b);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(b.cmp(&a), Ordering::Greater);
+        assert_eq!(a.cmp(&a.clone()), Ordering::Equal);
+        assert_eq!(
+            [a, b]
+                .into_iter()
+                .collect::<std::collections::BTreeSet<_>>()
+                .len(),
+            2
+        );
+
+        // Large enough for an unstable sort to reorder equal keys
+        let object = (0..200u64)
+            .map(|n| {
+                (
+                    Key::Borrowed(["b", "a"][n as usize % 2]),
+                    Value::Number(n.into()),
+                )
+            })
+            .collect::<ObjectAsVec<'_, Null, Null>>();
+        let values = super::sorted_entries(&object)
+            .into_iter()
+            .map(|(_, value)| value.as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            (1..200)
+                .step_by(2)
+                .chain((0..200).step_by(2))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn remove_order() {
+        let object: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("c", 3u64);
+
+        let mut swapped = object.clone();
+        assert_eq!(
+            swapped.remove(&"a".into()).and_then(|v| v.as_u64()),
+            Some(1)
+        );
+        assert_eq!(
+            swapped.keys().map(|k| k.to_string()).collect::<Vec<_>>(),
+            ["c", "b"]
+        );
+
+        let mut shifted = object;
+        assert_eq!(
+            shifted.shift_remove(&"a".into()).and_then(|v| v.as_u64()),
+            Some(1)
+        );
+        assert_eq!(shifted.shift_remove(&"a".into()), None);
+        assert_eq!(
+            shifted.keys().map(|k| k.to_string()).collect::<Vec<_>>(),
+            ["b", "c"]
+        );
+    }
+
+    #[test]
+    fn get_disjoint_mut_duplicate_entry() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new().with_key_value("a", 1u64);
+        obj.insert_unchecked("a", 2u64);
+
+        // Matches the first entry, as `get` does
+        let [a] = obj.get_disjoint_mut([&Key::Borrowed("a")]);
+        *a.unwrap() = Value::Null;
+        assert_eq!(obj.get(&Key::Borrowed("a")), Some(&Value::Null));
+        assert_eq!(obj.values().nth(1).and_then(|v| v.as_u64()), Some(2));
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("c", 3u64);
+
+        let keys = [Key::Borrowed("c"), Key::Borrowed("x"), Key::Borrowed("a")];
+        let mut values = obj.get_many_mut(&keys.iter().collect::<Vec<_>>());
+        assert_eq!(values.len(), 3);
+        assert!(values[1].is_none());
+        for value in values.iter_mut().flatten() {
+            **value = Value::Null;
+        }
+
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", Value::Null)
+                .with_key_value("b", 2u64)
+                .with_key_value("c", Value::Null)
+        );
+        assert!(obj.get_many_mut(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key \"a\" requested")]
+    fn get_many_mut_duplicate_key() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new().with_key_value("a", 1u64);
+        obj.get_many_mut(&[
+            &Key::Borrowed("a"),
+            &Key::Borrowed("b"),
+            &Key::Owned("a".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn reserve_and_shrink() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new();
+        obj.reserve(16);
+        assert!(obj.capacity() >= 16);
+
+        obj.insert("a", 1u64);
+        obj.insert("b", 2u64);
+        obj.shrink_to_fit();
+        assert!(obj.capacity() >= 2 && obj.capacity() < 16);
+    }
+
+    #[test]
+    fn reorder_keys() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("id", 3u64)
+            .with_key_value("c", 4u64);
+
+        assert_eq!(obj.position(&Key::Borrowed("id")), Some(2));
+        assert!(obj.move_key_to_front(&Key::Borrowed("id")));
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("id", 3u64)
+                .with_key_value("a", 1u64)
+                .with_key_value("b", 2u64)
+                .with_key_value("c", 4u64)
+        );
+
+        assert!(obj.move_key_to_back(&Key::Borrowed("a")));
+        obj.swap(0, 1);
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("b", 2u64)
+                .with_key_value("id", 3u64)
+                .with_key_value("c", 4u64)
+                .with_key_value("a", 1u64)
+        );
+
+        assert!(!obj.move_key_to_front(&Key::Borrowed("missing")));
+        assert_eq!(obj.position(&Key::Borrowed("missing")), None);
+    }
+
+    #[test]
+    fn find_key_by_value() {
+        let obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", "id1")
+            .with_key_value("b", "id2")
+            .with_key_value("c", "id2");
+
+        assert!(obj.contains_value(&Value::Str("id1".into())));
+        assert!(!obj.contains_value(&Value::Str("id3".into())));
+        assert_eq!(
+            obj.find_key_by_value(&Value::Str("id2".into())),
+            Some(&Key::Borrowed("b"))
+        );
+        assert_eq!(obj.find_key_by_value(&Value::Str("id3".into())), None);
+    }
+
+    #[test]
+    fn dedup_keys() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("a", 3u64);
+        obj.dedup_keys();
+
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", 3u64)
+                .with_key_value("b", 2u64)
+        );
+    }
+
+    #[test]
+    fn debug_output() {
+        let obj: ObjectAsVec<'_, TestProp, Null> = ObjectAsVec::new()
+            .with_key_value(Key::Property(TestProp::Id), 1u64)
+            .with_key_value(Key::Borrowed("tags"), Value::Array(vec![true.into()]))
+            .with_key_value(Key::Owned("name".to_string()), "Inbox");
+
+        assert_eq!(
+            format!("{obj:?}"),
+            r#"{"id": Number(1), "tags": Array [Bool(true)], "name": Str("Inbox")}"#
+        );
+        assert_eq!(
+            format!("{:?}", Value::Object(obj)),
+            r#"Object {"id": Number(1), "tags": Array [Bool(true)], "name": Str("Inbox")}"#
+        );
+    }
+
+    #[test]
+    fn merge_with() {
+        let mut obj: ObjectAsVec<'_, Null, Null> = ObjectAsVec::new()
+            .with_key_value("a", 1u64)
+            .with_key_value("b", 5u64)
+            .with_key_value("c", "text");
+        let other = ObjectAsVec::new()
+            .with_key_value("d", 4u64)
+            .with_key_value("b", 2u64)
+            .with_key_value("a", 3u64);
+
+        let mut conflicts = Vec::new();
+        obj.merge_with(other, |key, existing, incoming| {
+            conflicts.push(key.to_string().into_owned());
+            if incoming.as_u64() > existing.as_u64() {
+                incoming
+            } else {
+                existing
+            }
+        });
+
+        assert_eq!(conflicts, vec!["b", "a"]);
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value("a", 3u64)
+                .with_key_value("b", 5u64)
+                .with_key_value("c", "text")
+                .with_key_value("d", 4u64)
+        );
+    }
+
+    #[test]
+    fn filter_map_values() {
+        let obj: ObjectAsVec<'_, TestProp, Null> = ObjectAsVec::new()
+            .with_key_value(Key::Property(TestProp::Name), "inbox")
+            .with_key_value(Key::Borrowed("parentId"), Value::Null)
+            .with_key_value(Key::Borrowed("sortOrder"), 2u64)
+            .with_key_value(Key::Borrowed("role"), "Archive");
+
+        let obj = obj.filter_map_values(|key, value| match value {
+            Value::Null => None,
+            Value::Str(text) => Some((key, Value::Str(text.to_uppercase().into()))),
+            value => Some((key, value)),
+        });
+
+        assert_eq!(
+            obj,
+            ObjectAsVec::new()
+                .with_key_value(Key::Property(TestProp::Name), "INBOX")
+                .with_key_value(Key::Borrowed("sortOrder"), 2u64)
+                .with_key_value(Key::Borrowed("role"), "ARCHIVE")
+        );
+
+        let obj = obj.filter_map_values(|key, value| {
+            matches!(value, Value::Str(_))
+                .then(|| (Key::Owned(format!("x-{}", key.to_string())), value))
+        });
+        assert_eq!(
+            obj.key_strings().collect::<Vec<_>>(),
+            vec!["x-name", "x-role"]
+        );
+    }
+}
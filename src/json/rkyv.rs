@@ -4,11 +4,13 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use crate::{Element, Property, Value};
+use crate::{Element, Key, ObjectAsVec, Property, Value};
 use rkyv::{
+    collections::swiss_table::ArchivedHashMap,
     option::ArchivedOption,
     rend::{u32_le, u64_le},
     string::ArchivedString,
+    vec::ArchivedVec,
 };
 
 impl<'ctx, P: Property, E: Element, T> From<&ArchivedOption<T>> for Value<'ctx, P, E>
@@ -23,6 +25,38 @@ where
     }
 }
 
+impl<'ctx, P: Property, E: Element, T> From<&ArchivedVec<T>> for Value<'ctx, P, E>
+where
+    for<'x> &'x T: Into<Value<'ctx, P, E>>,
+{
+    fn from(value: &ArchivedVec<T>) -> Self {
+        let mut arr = Vec::with_capacity(value.len());
+        for item in value.iter() {
+            arr.push(item.into());
+        }
+        Value::Array(arr)
+    }
+}
+
+impl<'ctx, P: Property, E: Element, K, V, H> From<&ArchivedHashMap<K, V, H>> for Value<'ctx, P, E>
+where
+    K: AsRef<str>,
+    for<'x> &'x V: Into<Value<'ctx, P, E>>,
+{
+    fn from(value: &ArchivedHashMap<K, V, H>) -> Self {
+        let mut obj = ObjectAsVec::with_capacity(value.len());
+        for (key, value) in value.iter() {
+            let key = key.as_ref();
+            let key = match P::try_parse(None, key) {
+                Some(prop) => Key::Property(prop),
+                None => Key::Owned(key.to_string()),
+            };
+            obj.insert_unchecked(key, value);
+        }
+        Value::Object(obj)
+    }
+}
+
 impl<'ctx, P: Property, E: Element> From<&ArchivedString> for Value<'ctx, P, E> {
     fn from(value: &ArchivedString) -> Self {
         Value::Str(value.to_string().into())
@@ -40,3 +74,41 @@ impl<'ctx, P: Property, E: Element> From<&u64_le> for Value<'ctx, P, E> {
         Value::Number(u64::from(value).into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Null, Value};
+    use rkyv::{
+        collections::swiss_table::ArchivedHashMap, rancor::Error, rend::u64_le,
+        string::ArchivedString, vec::ArchivedVec,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn archived_vec_to_value() {
+        let list = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let bytes = rkyv::to_bytes::<Error>(&list).unwrap();
+        let archived = rkyv::access::<ArchivedVec<ArchivedString>, Error>(&bytes).unwrap();
+
+        let value: Value<'_, Null, Null> = archived.into();
+        assert_eq!(
+            value,
+            Value::Array(vec!["a".into(), "b".into(), "c".into()])
+        );
+    }
+
+    #[test]
+    fn archived_map_to_value() {
+        let mut map = HashMap::new();
+        map.insert("one".to_string(), 1u64);
+        map.insert("two".to_string(), 2u64);
+        let bytes = rkyv::to_bytes::<Error>(&map).unwrap();
+        let archived =
+            rkyv::access::<ArchivedHashMap<ArchivedString, u64_le>, Error>(&bytes).unwrap();
+
+        let value: Value<'_, Null, Null> = archived.into();
+        assert_eq!(value.as_object().unwrap().len(), 2);
+        assert_eq!(value.get("one"), &Value::Number(1u64.into()));
+        assert_eq!(value.get("two"), &Value::Number(2u64.into()));
+    }
+}
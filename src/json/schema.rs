@@ -0,0 +1,268 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::fmt::{Display, Formatter};
+
+use crate::{Element, JsonPointer, JsonPointerItem, Key, ObjectAsVec, Property, Value};
+
+/// A lightweight description of the expected shape of a value.
+///
+/// This is not JSON Schema: it only checks value types, required object properties and
+/// array item types, which is enough to describe JMAP objects. Object properties that are
+/// not declared in the schema are accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schema<P: Property> {
+    /// Accepts any value.
+    Any,
+    Null,
+    Bool,
+    Number,
+    /// Accepts strings and elements.
+    String,
+    /// An array whose items all match the schema.
+    Array(Box<Schema<P>>),
+    Object(ObjectSchema<P>),
+    /// Accepts `null` or a value matching the schema.
+    Nullable(Box<Schema<P>>),
+}
+
+/// The properties expected in an object, see [`Schema::Object`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ObjectSchema<P: Property> {
+    properties: Vec<(Key<'static, P>, Schema<P>, bool)>,
+}
+
+/// A mismatch between a value and a [`Schema`], located by a pointer to the offending node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError<P: Property> {
+    pub pointer: JsonPointer<P>,
+    pub message: String,
+}
+
+impl<P: Property> Schema<P> {
+    pub fn array(items: Schema<P>) -> Self {
+        Schema::Array(Box::new(items))
+    }
+
+    pub fn object(object: ObjectSchema<P>) -> Self {
+        Schema::Object(object)
+    }
+
+    pub fn nullable(self) -> Self {
+        Schema::Nullable(Box::new(self))
+    }
+
+    /// Validates `value` against the schema, returning every mismatch found.
+    pub fn validate<E: Element>(
+        &self,
+        value: &Value<'_, P, E>,
+    ) -> Result<(), Vec<ValidationError<P>>> {
+        let mut errors = Vec::new();
+        self.validate_at(value, &mut Vec::new(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_at<E: Element>(
+        &self,
+        value: &Value<'_, P, E>,
+        path: &mut Vec<JsonPointerItem<P>>,
+        errors: &mut Vec<ValidationError<P>>,
+    ) {
+        match (self, value) {
+            (Schema::Any, _)
+            | (Schema::Null, Value::Null)
+            | (Schema::Bool, Value::Bool(_))
+            | (Schema::Number, Value::Number(_))
+            | (Schema::String, Value::Str(_) | Value::Element(_))
+            | (Schema::Nullable(_), Value::Null) => {}
+            (Schema::Nullable(schema), value) => schema.validate_at(value, path, errors),
+            (Schema::Array(schema), Value::Array(items)) => {
+                for (idx, item) in items.iter().enumerate() {
+                    path.push(JsonPointerItem::Number(idx as u64));
+                    schema.validate_at(item, path, errors);
+                    path.pop();
+                }
+            }
+            (Schema::Object(schema), Value::Object(object)) => {
+                schema.validate_at(object, path, errors);
+            }
+            (schema, value) => errors.push(ValidationError::new(
+                path,
+                format!(
+                    "expected {}, found {}",
+                    schema.type_name(),
+                    value_type_name(value)
+                ),
+            )),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Schema::Any => "any value",
+            Schema::Null => "null",
+            Schema::Bool => "boolean",
+            Schema::Number => "number",
+            Schema::String => "string",
+            Schema::Array(_) => "array",
+            Schema::Object(_) => "object",
+            Schema::Nullable(schema) => schema.type_name(),
+        }
+    }
+}
+
+impl<P: Property> ObjectSchema<P> {
+    pub fn new() -> Self {
+        ObjectSchema {
+            properties: Vec::new(),
+        }
+    }
+
+    /// Declares a property that has to be present and match `schema`.
+    pub fn required(mut self, key: impl Into<Key<'static, P>>, schema: Schema<P>) -> Self {
+        self.properties.push((key.into(), schema, true));
+        self
+    }
+
+    /// Declares a property that has to match `schema` when present.
+    pub fn optional(mut self, key: impl Into<Key<'static, P>>, schema: Schema<P>) -> Self {
+        self.properties.push((key.into(), schema, false));
+        self
+    }
+
+    fn validate_at<E: Element>(
+        &self,
+        object: &ObjectAsVec<'_, P, E>,
+        path: &mut Vec<JsonPointerItem<P>>,
+        errors: &mut Vec<ValidationError<P>>,
+    ) {
+        for (key, schema, required) in &self.properties {
+            path.push(JsonPointerItem::Key(key.clone()));
+            match object.get(key) {
+                Some(value) => schema.validate_at(value, path, errors),
+                None if *required => {
+                    errors.push(ValidationError::new(path, "missing required property"))
+                }
+                None => {}
+            }
+            path.pop();
+        }
+    }
+}
+
+impl<P: Property> From<ObjectSchema<P>> for Schema<P> {
+    fn from(object: ObjectSchema<P>) -> Self {
+        Schema::Object(object)
+    }
+}
+
+impl<P: Property> ValidationError<P> {
+    fn new(path: &[JsonPointerItem<P>], message: impl Into<String>) -> Self {
+        let pointer = if path.is_empty() {
+            JsonPointer::new(vec![JsonPointerItem::Root])
+        } else {
+            JsonPointer::new(path.to_vec())
+        };
+        ValidationError {
+            pointer,
+            message: message.into(),
+        }
+    }
+}
+
+impl<P: Property> Display for ValidationError<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at '{}'", self.message, self.pointer)
+    }
+}
+
+impl<P: Property> core::error::Error for ValidationError<P> {}
+
+fn value_type_name<P: Property, E: Element>(value: &Value<'_, P, E>) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::Str(_) | Value::Element(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ObjectSchema, Schema, ValidationError};
+    use crate::{JsonPointer, Null, Value};
+
+    fn email_schema() -> Schema<Null> {
+        Schema::object(
+            ObjectSchema::new()
+                .required("id", Schema::String)
+                .optional("subject", Schema::String.nullable())
+                .required("size", Schema::Number)
+                .optional(
+                    "to",
+                    Schema::array(Schema::object(
+                        ObjectSchema::new()
+                            .optional("name", Schema::String.nullable())
+                            .required("email", Schema::String),
+                    )),
+                ),
+        )
+    }
+
+    #[test]
+    fn validate_valid() {
+        for json in [
+            r#"{"id": "a", "size": 10}"#,
+            r#"{"id": "a", "size": 10, "subject": null, "to": [], "extra": true}"#,
+            r#"{"id": "a", "size": 10, "to": [{"email": "a@b"}, {"name": "c", "email": "d@e"}]}"#,
+        ] {
+            let value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+            assert_eq!(value.validate_against(&email_schema()), Ok(()), "{json}");
+        }
+    }
+
+    #[test]
+    fn validate_invalid() {
+        for (json, expected) in [
+            ("[]", vec![("", "expected object, found array")]),
+            (
+                r#"{"size": "10"}"#,
+                vec![
+                    ("id", "missing required property"),
+                    ("size", "expected number, found string"),
+                ],
+            ),
+            (
+                r#"{"id": "a", "size": 1, "subject": 5}"#,
+                vec![("subject", "expected string, found number")],
+            ),
+            (
+                r#"{"id": "a", "size": 1, "to": [{"email": "a@b"}, {"name": null}, 3]}"#,
+                vec![
+                    ("to/1/email", "missing required property"),
+                    ("to/2", "expected object, found number"),
+                ],
+            ),
+        ] {
+            let value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+            let expected = expected
+                .into_iter()
+                .map(|(pointer, message)| ValidationError {
+                    pointer: JsonPointer::parse(pointer),
+                    message: message.to_string(),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(email_schema().validate(&value), Err(expected), "{json}");
+        }
+    }
+}
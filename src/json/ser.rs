@@ -4,10 +4,46 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
+#[cfg(feature = "raw_number")]
+use crate::json::num::RawNumber;
 use crate::json::num::{N, Number};
-use crate::json::value::Value;
+use crate::json::value::{ElementRepr, Value};
 use crate::{Element, Map, Property};
-use serde::ser::{Serialize, Serializer};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Serializes a `Value` with the entries of every object emitted in key order,
+/// without mutating or cloning the underlying value.
+#[derive(Debug, Clone, Copy)]
+pub struct SortedValue<'a, 'ctx, P: Property, E: Element>(pub &'a Value<'ctx, P, E>);
+
+/// Serializes a `Value` leaving out the entries of every object whose value is `null`.
+/// This only shapes the output, the underlying value is not modified. Null elements of
+/// arrays are kept.
+#[derive(Debug, Clone, Copy)]
+pub struct SkipNulls<'a, 'ctx, P: Property, E: Element>(pub &'a Value<'ctx, P, E>);
+
+/// Options for [`Value::serialize_with`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Serializes elements using [`Element::to_value`] when it returns a value, rather than
+    /// as their string form.
+    pub expand_elements: bool,
+    /// Serializes integers whose magnitude exceeds this value as strings, so clients that
+    /// parse numbers as doubles do not lose precision. Floats are left as-is.
+    pub max_safe_integer: Option<u64>,
+}
+
+impl SerializeOptions {
+    /// The largest integer JavaScript represents exactly, `Number.MAX_SAFE_INTEGER`.
+    pub const JS_MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+}
+
+struct ValueWithOptions<'a, 'ctx, P: Property, E: Element>(&'a Value<'ctx, P, E>, SerializeOptions);
 
 impl<P: Property, E: Element> Serialize for Value<'_, P, E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -21,7 +57,10 @@ impl<P: Property, E: Element> Serialize for Value<'_, P, E> {
             Value::Str(s) => serializer.serialize_str(s),
             Value::Array(v) => serializer.collect_seq(v),
             Value::Object(m) => m.serialize(serializer),
-            Value::Element(e) => serializer.serialize_str(e.to_cow().as_ref()),
+            Value::Element(e) => match e.serialize_as() {
+                ElementRepr::String(s) => serializer.serialize_str(s.as_ref()),
+                ElementRepr::Value(value) => value.serialize(serializer),
+            },
         }
     }
 }
@@ -34,6 +73,268 @@ impl<P: Property, E: Element> Serialize for Map<'_, P, E> {
     }
 }
 
+impl<P: Property, E: Element> Serialize for SortedValue<'_, '_, P, E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Value::Array(v) => serializer.collect_seq(v.iter().map(SortedValue)),
+            Value::Object(m) => {
+                let mut entries = m
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect::<Vec<_>>();
+                // Stable, so duplicate keys keep their insertion order
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k.as_ref(), &SortedValue(v))?;
+                }
+                map.end()
+            }
+            Value::Element(e) => match e.serialize_as() {
+                ElementRepr::String(s) => serializer.serialize_str(s.as_ref()),
+                ElementRepr::Value(value) => SortedValue(&value).serialize(serializer),
+            },
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+impl<P: Property, E: Element> Serialize for SkipNulls<'_, '_, P, E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0 {
+            Value::Array(v) => serializer.collect_seq(v.iter().map(SkipNulls)),
+            Value::Object(m) => serializer.collect_map(
+                m.iter()
+                    .filter(|(_, v)| !matches!(v, Value::Null))
+                    .map(|(k, v)| (k, SkipNulls(v))),
+            ),
+            Value::Element(e) => match e.serialize_as() {
+                ElementRepr::String(s) => serializer.serialize_str(s.as_ref()),
+                ElementRepr::Value(value) => SkipNulls(&value).serialize(serializer),
+            },
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'ctx, P: Property, E: Element> Value<'ctx, P, E> {
+    /// Serializes the value leaving out null object entries, see [`SkipNulls`].
+    pub fn serialize_skip_nulls<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SkipNulls(self).serialize(serializer)
+    }
+
+    /// Serializes the value with the entries of every object emitted in key order.
+    /// The default `Serialize` implementation preserves insertion order.
+    pub fn serialize_sorted<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SortedValue(self).serialize(serializer)
+    }
+
+    /// Serializes the value as minified JSON, writing it directly from the `Serialize`
+    /// implementation without an intermediate `serde_json::Value`. Object entries are
+    /// emitted in insertion order. On a nested document this makes a handful of allocations
+    /// for the output buffer where `to_string()` makes one per node.
+    pub fn to_json_bytes(&self) -> Vec<u8> {
+        // Keys are always strings, so serializing a value cannot fail
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /// Serializes the value as a minified JSON string, see [`Value::to_json_bytes`].
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl<P: Property, E: Element> Serialize for ValueWithOptions<'_, '_, P, E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let opts = self.1;
+        match self.0 {
+            Value::Array(v) => {
+                serializer.collect_seq(v.iter().map(|item| ValueWithOptions(item, opts)))
+            }
+            Value::Object(m) => {
+                let mut map = serializer.serialize_map(Some(m.len()))?;
+                for (k, v) in m.iter() {
+                    map.serialize_entry(k, &ValueWithOptions(v, opts))?;
+                }
+                map.end()
+            }
+            Value::Element(e) => match opts.expand_elements.then(|| e.to_value()).flatten() {
+                Some(value) => ValueWithOptions(&value, opts).serialize(serializer),
+                None => match e.serialize_as() {
+                    ElementRepr::String(s) => serializer.serialize_str(s.as_ref()),
+                    ElementRepr::Value(value) => {
+                        ValueWithOptions(&value, opts).serialize(serializer)
+                    }
+                },
+            },
+            Value::Number(n) => match (&n.n, opts.max_safe_integer) {
+                (N::PosInt(v), Some(max)) if *v > max => serializer.collect_str(v),
+                (N::NegInt(v), Some(max)) if v.unsigned_abs() > max => serializer.collect_str(v),
+                _ => n.serialize(serializer),
+            },
+            value => value.serialize(serializer),
+        }
+    }
+}
+
+impl<'ctx, P: Property, E: Element> Value<'ctx, P, E> {
+    /// Serializes the value using the given options. With the default options this produces
+    /// the same output as the `Serialize` implementation.
+    pub fn serialize_with<S>(
+        &self,
+        serializer: S,
+        opts: SerializeOptions,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ValueWithOptions(self, opts).serialize(serializer)
+    }
+
+    /// Serializes the value with integers beyond [`SerializeOptions::JS_MAX_SAFE_INTEGER`]
+    /// written as strings, for JavaScript clients.
+    pub fn serialize_js_safe<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serialize_with(
+            serializer,
+            SerializeOptions {
+                max_safe_integer: Some(SerializeOptions::JS_MAX_SAFE_INTEGER),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl<'ctx, P: Property, E: Element> Value<'ctx, P, E> {
+    /// Serializes the value as canonical JSON following RFC 8785 (JCS).
+    ///
+    /// Object keys are sorted by their UTF-16 code units, as required by JCS, rather than
+    /// by bytes. Floats use the shortest representation that round-trips, formatted as
+    /// ECMAScript would. Integers are written as-is, so values above 2^53 are kept exact
+    /// instead of being rounded to a double.
+    pub fn to_canonical_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_canonical(self, &mut buf);
+        buf
+    }
+}
+
+fn write_canonical<P: Property, E: Element>(value: &Value<'_, P, E>, buf: &mut Vec<u8>) {
+    match value {
+        Value::Null => buf.extend_from_slice(b"null"),
+        Value::Bool(true) => buf.extend_from_slice(b"true"),
+        Value::Bool(false) => buf.extend_from_slice(b"false"),
+        Value::Number(n) => match n.n {
+            N::PosInt(n) => buf.extend_from_slice(n.to_string().as_bytes()),
+            N::NegInt(n) => buf.extend_from_slice(n.to_string().as_bytes()),
+            N::Float(n) => write_canonical_float(n, buf),
+        },
+        Value::Str(s) => write_canonical_str(s, buf),
+        Value::Element(e) => match e.serialize_as() {
+            ElementRepr::String(s) => write_canonical_str(s.as_ref(), buf),
+            ElementRepr::Value(value) => write_canonical(&value, buf),
+        },
+        Value::Array(arr) => {
+            buf.push(b'[');
+            for (pos, item) in arr.iter().enumerate() {
+                if pos > 0 {
+                    buf.push(b',');
+                }
+                write_canonical(item, buf);
+            }
+            buf.push(b']');
+        }
+        Value::Object(obj) => {
+            let mut entries = obj
+                .iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            buf.push(b'{');
+            for (pos, (key, value)) in entries.into_iter().enumerate() {
+                if pos > 0 {
+                    buf.push(b',');
+                }
+                write_canonical_str(key.as_ref(), buf);
+                buf.push(b':');
+                write_canonical(value, buf);
+            }
+            buf.push(b'}');
+        }
+    }
+}
+
+fn write_canonical_str(s: &str, buf: &mut Vec<u8>) {
+    // serde_json only escapes what JSON requires, using lowercase hex digits,
+    // which matches the JCS string serialization rules.
+    if let Ok(escaped) = serde_json::to_vec(s) {
+        buf.extend_from_slice(&escaped);
+    }
+}
+
+fn write_canonical_float(n: f64, buf: &mut Vec<u8>) {
+    if n == 0.0 || !n.is_finite() {
+        buf.push(b'0');
+        return;
+    }
+
+    // Rust's exponential formatting yields the shortest round-trip digits,
+    // which are then laid out following ECMAScript's Number::toString.
+    let formatted = format!("{:e}", n.abs());
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    let n_pos = exponent.parse::<i32>().unwrap_or(0) + 1;
+
+    let out = if k <= n_pos && n_pos <= 21 {
+        format!("{digits}{}", "0".repeat((n_pos - k) as usize))
+    } else if 0 < n_pos && n_pos <= 21 {
+        format!(
+            "{}.{}",
+            &digits[..n_pos as usize],
+            &digits[n_pos as usize..]
+        )
+    } else if -6 < n_pos && n_pos <= 0 {
+        format!("0.{}{digits}", "0".repeat((-n_pos) as usize))
+    } else {
+        let sign = if n_pos - 1 < 0 { '-' } else { '+' };
+        if k == 1 {
+            format!("{digits}e{sign}{}", (n_pos - 1).abs())
+        } else {
+            format!(
+                "{}.{}e{sign}{}",
+                &digits[..1],
+                &digits[1..],
+                (n_pos - 1).abs()
+            )
+        }
+    };
+
+    if n < 0.0 {
+        buf.push(b'-');
+    }
+    buf.extend_from_slice(out.as_bytes());
+}
+
 impl Serialize for Number {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -47,9 +348,26 @@ impl Serialize for Number {
     }
 }
 
+#[cfg(feature = "raw_number")]
+impl Serialize for RawNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Written verbatim by serde_json, other serializers see a RawValue struct
+        serde_json::value::RawValue::from_string(self.as_str().to_string())
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::SkipNulls;
+    use super::{SerializeOptions, SortedValue};
     use crate::Null;
+    use crate::{Element, ElementRepr, Key, ObjectAsVec, Value};
+    use std::borrow::Cow;
 
     #[test]
     fn serialize_json_test() {
@@ -60,4 +378,278 @@ mod tests {
         let deser1: String = serde_json::to_string(&val1).unwrap();
         assert_eq!(deser1, json_obj);
     }
+
+    #[test]
+    fn canonical_json_test() {
+        // Test vector from RFC 8785, section 3.2.2. The numbers are parsed separately
+        // as serde_json's default float parser is not correctly rounded.
+        let json_obj = r#"{
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#;
+        let mut val: crate::Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+        val.as_object_mut().unwrap().insert(
+            "numbers",
+            [
+                "333333333.33333329",
+                "1E30",
+                "4.50",
+                "2e-3",
+                "0.000000000000000000000000001",
+            ]
+            .map(|n| n.parse::<f64>().unwrap())
+            .to_vec(),
+        );
+        assert_eq!(
+            String::from_utf8(val.to_canonical_bytes()).unwrap(),
+            concat!(
+                r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"#,
+                r#""string":"€$\u000f\nA'B\"\\\\\"/"}"#
+            )
+        );
+
+        // Keys are sorted by UTF-16 code units rather than by bytes
+        let json_obj = r#"{"\ufb01": 1, "\ud83d\ude00": 2, "a": [5.0, -0.0, -12.5, 1e21, 1e-7]}"#;
+        let val: crate::Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+        assert_eq!(
+            String::from_utf8(val.to_canonical_bytes()).unwrap(),
+            r#"{"a":[5,0,-12.5,1e+21,1e-7],"😀":2,"ﬁ":1}"#
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Size(u64);
+
+    impl Element for Size {
+        type Property = Null;
+
+        fn try_parse<P>(_: &Key<'_, Null>, value: &str) -> Option<Self> {
+            value.strip_suffix(" bytes")?.parse().ok().map(Size)
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            format!("{} bytes", self.0).into()
+        }
+
+        fn to_value(&self) -> Option<Value<'static, Null, Self>> {
+            (self.0 > 0).then(|| {
+                Value::Object(
+                    ObjectAsVec::new()
+                        .with_key_value("value", self.0)
+                        .with_key_value("unit", "bytes"),
+                )
+            })
+        }
+    }
+
+    #[test]
+    fn serialize_with_options_test() {
+        let val = Value::<'_, Null, Size>::Array(vec![
+            Value::Element(Size(10)),
+            Value::Element(Size(0)),
+            Value::Object(ObjectAsVec::new().with_key_value("size", Value::Element(Size(2)))),
+        ]);
+
+        let serialize = |opts| {
+            let mut buf = Vec::new();
+            val.serialize_with(&mut serde_json::Serializer::new(&mut buf), opts)
+                .unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        assert_eq!(
+            serialize(SerializeOptions::default()),
+            serde_json::to_string(&val).unwrap()
+        );
+        assert_eq!(
+            serialize(SerializeOptions::default()),
+            r#"["10 bytes","0 bytes",{"size":"2 bytes"}]"#
+        );
+        assert_eq!(
+            serialize(SerializeOptions {
+                expand_elements: true,
+                ..Default::default()
+            }),
+            concat!(
+                r#"[{"value":10,"unit":"bytes"},"0 bytes","#,
+                r#"{"size":{"value":2,"unit":"bytes"}}]"#
+            )
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct ResultRef(String);
+
+    impl Element for ResultRef {
+        type Property = Null;
+
+        fn try_parse<P>(_: &Key<'_, Null>, _: &str) -> Option<Self> {
+            None
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            format!("#{}", self.0).into()
+        }
+
+        fn serialize_as(&self) -> ElementRepr<Self> {
+            ElementRepr::Value(Value::Object(
+                ObjectAsVec::new().with_key_value("resultOf", self.0.clone()),
+            ))
+        }
+
+        fn try_parse_object(
+            _: &Key<'_, Null>,
+            object: &ObjectAsVec<'_, Null, Self>,
+        ) -> Option<Self> {
+            match object.as_vec().as_slice() {
+                [(key, Value::Str(id))] if key.to_string() == "resultOf" => {
+                    Some(ResultRef(id.to_string()))
+                }
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_element_repr_test() {
+        let val = Value::<'_, Null, ResultRef>::Object(
+            ObjectAsVec::new()
+                .with_key_value("ids", Value::Element(ResultRef("c1".into())))
+                .with_key_value(
+                    "list",
+                    Value::Array(vec![Value::Element(ResultRef("c2".into()))]),
+                )
+                .with_key_value(
+                    "other",
+                    Value::Object(ObjectAsVec::new().with_key_value("id", "x")),
+                ),
+        );
+        let expected = concat!(
+            r#"{"ids":{"resultOf":"c1"},"list":[{"resultOf":"c2"}],"#,
+            r#""other":{"id":"x"}}"#
+        );
+
+        let json = val.to_json_string();
+        assert_eq!(json, expected);
+        assert_eq!(val.to_string(), expected);
+        assert_eq!(
+            String::from_utf8(val.to_canonical_bytes()).unwrap(),
+            expected
+        );
+        assert_eq!(Value::parse_json(&json).unwrap(), val);
+
+        // Objects at the root have no key and are left as-is
+        let root = Value::<'_, Null, ResultRef>::parse_json(r#"{"resultOf":"c1"}"#).unwrap();
+        assert!(root.is_object());
+    }
+
+    #[test]
+    fn serialize_js_safe_test() {
+        let json_obj = concat!(
+            r#"{"big":9007199254740992,"safe":9007199254740991,"neg":-9007199254740993,"#,
+            r#""float":1e300,"list":[18446744073709551615,1]}"#
+        );
+        let val: Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+
+        let mut buf = Vec::new();
+        val.serialize_js_safe(&mut serde_json::Serializer::new(&mut buf))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            concat!(
+                r#"{"big":"9007199254740992","safe":9007199254740991,"neg":"-9007199254740993","#,
+                r#""float":1e+300,"list":["18446744073709551615",1]}"#
+            )
+        );
+
+        let mut buf = Vec::new();
+        val.serialize_with(
+            &mut serde_json::Serializer::new(&mut buf),
+            SerializeOptions {
+                max_safe_integer: Some(1),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            String::from_utf8(buf)
+                .unwrap()
+                .ends_with(r#"["18446744073709551615",1]}"#)
+        );
+    }
+
+    #[test]
+    fn serialize_sorted_test() {
+        let json_obj = r#"{"b":[{"z":1,"a":2}],"a":{"y":true,"x":null},"c":"d"}"#;
+
+        let val: crate::Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+        assert_eq!(
+            serde_json::to_string(&SortedValue(&val)).unwrap(),
+            r#"{"a":{"x":null,"y":true},"b":[{"a":2,"z":1}],"c":"d"}"#
+        );
+
+        let mut out = Vec::new();
+        val.serialize_sorted(&mut serde_json::Serializer::new(&mut out))
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            r#"{"a":{"x":null,"y":true},"b":[{"a":2,"z":1}],"c":"d"}"#
+        );
+        assert_eq!(serde_json::to_string(&val).unwrap(), json_obj);
+
+        // Duplicate keys keep their insertion order
+        let mut object = ObjectAsVec::new().with_key_value("b", 1u64);
+        for n in 2..=200u64 {
+            object.insert_unchecked(if n % 2 == 0 { "a" } else { "b" }, n);
+        }
+        let val: Value<'_, Null, Null> = Value::Object(object);
+        let expected = format!(
+            "{{{},{}}}",
+            (2..=200)
+                .step_by(2)
+                .map(|n| format!(r#""a":{n}"#))
+                .collect::<Vec<_>>()
+                .join(","),
+            (1..=199)
+                .step_by(2)
+                .map(|n| format!(r#""b":{n}"#))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        assert_eq!(serde_json::to_string(&SortedValue(&val)).unwrap(), expected);
+        assert_eq!(
+            String::from_utf8(val.to_canonical_bytes()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn serialize_skip_nulls_test() {
+        let json_obj =
+            r#"{"a":null,"b":[null,{"c":null,"d":1}],"e":{"f":null},"g":{"h":[null]},"i":"j"}"#;
+
+        let val: crate::Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+        assert_eq!(serde_json::to_string(&val).unwrap(), json_obj);
+        assert_eq!(
+            serde_json::to_string(&SkipNulls(&val)).unwrap(),
+            r#"{"b":[null,{"d":1}],"e":{},"g":{"h":[null]},"i":"j"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&SkipNulls(&crate::Value::<'_, Null, Null>::Null)).unwrap(),
+            "null"
+        );
+        assert_eq!(val.to_json_string(), json_obj);
+    }
+
+    #[test]
+    fn to_json_string_test() {
+        let json_obj = r#"{"b":[{"z":1,"a":-2.5}],"a":{"y":true,"x":null},"c":"d\"e"}"#;
+
+        let val: crate::Value<'_, Null, Null> = serde_json::from_str(json_obj).unwrap();
+        assert_eq!(val.to_json_string(), json_obj);
+        assert_eq!(val.to_json_bytes(), json_obj.as_bytes());
+        assert_eq!(
+            val.to_string(),
+            r#"{"a":{"x":null,"y":true},"b":[{"a":-2.5,"z":1}],"c":"d\"e"}"#
+        );
+    }
 }
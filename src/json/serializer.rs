@@ -0,0 +1,644 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use alloc::borrow::Cow;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::marker::PhantomData;
+
+use crate::json::key::Key;
+use crate::json::num::N;
+use crate::{Element, ObjectAsVec, Property, Value};
+use serde::Serialize;
+use serde::de::value::Error;
+use serde::ser::{self, Error as _, Impossible};
+
+/// Serializes any `Serialize` type directly into an owned `Value` tree.
+///
+/// Since there is no key context available during generic serialization, strings are always
+/// produced as `Value::Str` and object keys as `Key::Owned`; no `Element` or `Property` parsing
+/// takes place. Non-finite floats are serialized as `null`.
+pub fn to_value<T, P, E>(value: &T) -> Result<Value<'static, P, E>, String>
+where
+    T: Serialize + ?Sized,
+    P: Property,
+    E: Element,
+{
+    value
+        .serialize(ValueSerializer::default())
+        .map_err(|err| err.to_string())
+}
+
+struct ValueSerializer<P, E>(PhantomData<(P, E)>);
+
+impl<P, E> Default for ValueSerializer<P, E> {
+    fn default() -> Self {
+        ValueSerializer(PhantomData)
+    }
+}
+
+impl<P: Property, E: Element> ser::Serializer for ValueSerializer<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec<P, E>;
+    type SerializeTuple = SerializeVec<P, E>;
+    type SerializeTupleStruct = SerializeVec<P, E>;
+    type SerializeTupleVariant = SerializeVariant<SerializeVec<P, E>>;
+    type SerializeMap = SerializeMap<P, E>;
+    type SerializeStruct = SerializeMap<P, E>;
+    type SerializeStructVariant = SerializeVariant<SerializeMap<P, E>>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        if v < 0 {
+            Ok(Value::Number(N::NegInt(v).into()))
+        } else {
+            Ok(Value::Number(N::PosInt(v as u64).into()))
+        }
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        if let Ok(v) = u64::try_from(v) {
+            self.serialize_u64(v)
+        } else if let Ok(v) = i64::try_from(v) {
+            self.serialize_i64(v)
+        } else {
+            Err(Error::custom("number out of range"))
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Number(N::PosInt(v).into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        u64::try_from(v)
+            .map_err(|_| Error::custom("number out of range"))
+            .and_then(|v| self.serialize_u64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        if v.is_finite() {
+            Ok(Value::Number(N::Float(v).into()))
+        } else {
+            Ok(Value::Null)
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Str(Cow::Owned(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Str(Cow::Owned(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Array(
+            v.iter()
+                .map(|&b| Value::Number(N::PosInt(b as u64).into()))
+                .collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut map = ObjectAsVec::with_capacity(1);
+        map.insert_unchecked(
+            Key::Owned(variant.to_string()),
+            value.serialize(ValueSerializer::default())?,
+        );
+        Ok(Value::Object(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(SerializeVec {
+            vec: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(SerializeMap {
+            map: ObjectAsVec::with_capacity(len.unwrap_or(0)),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+struct SerializeVec<P: Property, E: Element> {
+    vec: Vec<Value<'static, P, E>>,
+}
+
+impl<P: Property, E: Element> ser::SerializeSeq for SerializeVec<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.vec.push(value.serialize(ValueSerializer::default())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Value::Array(self.vec))
+    }
+}
+
+impl<P: Property, E: Element> ser::SerializeTuple for SerializeVec<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<P: Property, E: Element> ser::SerializeTupleStruct for SerializeVec<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeMap<P: Property, E: Element> {
+    map: ObjectAsVec<'static, P, E>,
+    next_key: Option<String>,
+}
+
+impl<P: Property, E: Element> ser::SerializeMap for SerializeMap<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.next_key = Some(key.serialize(MapKeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(
+            Key::Owned(key),
+            value.serialize(ValueSerializer::default())?,
+        );
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Value::Object(self.map))
+    }
+}
+
+impl<P: Property, E: Element> ser::SerializeStruct for SerializeMap<P, E> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.map.insert_unchecked(
+            Key::Owned(key.to_string()),
+            value.serialize(ValueSerializer::default())?,
+        );
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T, P: Property, E: Element> SerializeVariant<T>
+where
+    T: VariantInner<Ok = Value<'static, P, E>>,
+{
+    fn finish(self) -> Result<Value<'static, P, E>, Error> {
+        let mut map = ObjectAsVec::with_capacity(1);
+        map.insert_unchecked(Key::Owned(self.variant.to_string()), self.inner.end()?);
+        Ok(Value::Object(map))
+    }
+}
+
+trait VariantInner {
+    type Ok;
+
+    fn end(self) -> Result<Self::Ok, Error>;
+}
+
+impl<P: Property, E: Element> VariantInner for SerializeVec<P, E> {
+    type Ok = Value<'static, P, E>;
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl<P: Property, E: Element> VariantInner for SerializeMap<P, E> {
+    type Ok = Value<'static, P, E>;
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl<P: Property, E: Element> ser::SerializeTupleVariant for SerializeVariant<SerializeVec<P, E>> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+impl<P: Property, E: Element> ser::SerializeStructVariant for SerializeVariant<SerializeMap<P, E>> {
+    type Ok = Value<'static, P, E>;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        self.finish()
+    }
+}
+
+/// Serializes map keys into strings, accepting the same key types as `serde_json`.
+struct MapKeySerializer;
+
+fn key_must_be_a_string() -> Error {
+    Error::custom("key must be a string")
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(key_must_be_a_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use crate::{Null, Value};
+
+    use super::to_value;
+
+    #[derive(Serialize)]
+    enum Kind {
+        Personal,
+        Work { priority: u8 },
+    }
+
+    #[derive(Serialize)]
+    struct Contact {
+        name: String,
+        emails: Vec<String>,
+        age: Option<i32>,
+        kinds: Vec<Kind>,
+        tags: BTreeMap<u32, bool>,
+    }
+
+    #[test]
+    fn serialize_struct() {
+        let contact = Contact {
+            name: "John".to_string(),
+            emails: vec!["john@example.org".to_string()],
+            age: Some(42),
+            kinds: vec![Kind::Personal, Kind::Work { priority: 1 }],
+            tags: BTreeMap::from([(1, true)]),
+        };
+
+        let value: Value<'static, Null, Null> = to_value(&contact).unwrap();
+        assert_eq!(
+            value,
+            Value::parse_json(
+                r#"{"name": "John", "emails": ["john@example.org"], "age": 42,
+                    "kinds": ["Personal", {"Work": {"priority": 1}}], "tags": {"1": true}}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_scalars() {
+        assert_eq!(
+            to_value::<_, Null, Null>(&-1i8).unwrap(),
+            Value::Number((-1i64).into())
+        );
+        assert_eq!(to_value::<_, Null, Null>(&()).unwrap(), Value::Null);
+        assert_eq!(to_value::<_, Null, Null>(&f64::NAN).unwrap(), Value::Null);
+        assert!(to_value::<_, Null, Null>(&BTreeMap::from([((1, 2), 3)])).is_err());
+    }
+}
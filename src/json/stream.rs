@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use crate::{Element, Property, Value};
+use std::io::BufRead;
+
+/// Parses a stream of newline-delimited JSON values, yielding one owned `Value` per line.
+///
+/// Blank lines are skipped. A line that fails to parse, including one that is not valid
+/// UTF-8, yields an `Err` and iteration continues with the next line, while an I/O error
+/// yields an `Err` and ends the iteration.
+pub fn from_ndjson_reader<R, P, E>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<Value<'static, P, E>, String>>
+where
+    R: BufRead,
+    P: Property,
+    E: Element<Property = P>,
+{
+    let mut line = Vec::new();
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        while !done {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => done = true,
+                Ok(_) => {
+                    let json = line.trim_ascii();
+                    if !json.is_empty() {
+                        return Some(Value::parse_json_bytes(json).map(Value::into_owned));
+                    }
+                }
+                Err(err) => {
+                    done = true;
+                    return Some(Err(err.to_string()));
+                }
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::from_ndjson_reader;
+    use crate::{Null, Value};
+
+    #[test]
+    fn ndjson_reader() {
+        let input = "{\"a\": 1}\n\n  \n[1, 2]\n{invalid\n\"text\"";
+        let results = from_ndjson_reader::<_, Null, Null>(input.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0],
+            Ok(Value::parse_json(r#"{"a": 1}"#).unwrap().into_owned())
+        );
+        assert_eq!(
+            results[1],
+            Ok(Value::Array(vec![
+                Value::Number(1u64.into()),
+                Value::Number(2u64.into())
+            ]))
+        );
+        assert!(results[2].is_err());
+        assert_eq!(results[3], Ok(Value::Str("text".into())));
+    }
+
+    #[test]
+    fn ndjson_reader_invalid_utf8() {
+        let input = b"1\n\xff\n2\n";
+        let results = from_ndjson_reader::<_, Null, Null>(&input[..]).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok(Value::Number(1u64.into())));
+        assert!(results[1].is_err());
+        assert_eq!(results[2], Ok(Value::Number(2u64.into())));
+    }
+}
@@ -1,14 +1,28 @@
 use serde::Serialize;
 
+use crate::json::base64;
 use crate::json::index::Index;
 use crate::json::key::Key;
 use crate::json::num::{N, Number};
 pub use crate::json::object_vec::ObjectAsVec;
+use crate::json::object_vec::ObjectEntry;
+use crate::json::schema::{Schema, ValidationError};
+use crate::pointer::{JsonPointer, JsonPointerItem, JsonPointerIter};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
 use core::fmt;
+use core::fmt::{Debug, Display};
 use core::hash::Hash;
-use std::borrow::Cow;
-use std::fmt::{Debug, Display};
-use std::str::FromStr;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, DefaultHasher, Hasher},
+};
 
 /// Represents any valid JMAP value.
 #[derive(Clone, Eq, PartialEq, Hash, Default)]
@@ -26,6 +40,56 @@ pub enum Value<'ctx, P: Property, E: Element> {
 pub trait Property: Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash {
     fn try_parse(key: Option<&Key<'_, Self>>, value: &str) -> Option<Self>;
     fn to_cow(&self) -> Cow<'static, str>;
+
+    /// Returns every known property, or an empty slice if the set is open or was not
+    /// declared by the implementation.
+    ///
+    /// ```
+    /// use jmap_tools::{Key, Property};
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    /// enum EmailProperty {
+    ///     Subject,
+    ///     From,
+    /// }
+    ///
+    /// impl Property for EmailProperty {
+    ///     fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+    ///         match value {
+    ///             "subject" => Some(EmailProperty::Subject),
+    ///             "from" => Some(EmailProperty::From),
+    ///             _ => None,
+    ///         }
+    ///     }
+    ///
+    ///     fn to_cow(&self) -> Cow<'static, str> {
+    ///         match self {
+    ///             EmailProperty::Subject => "subject".into(),
+    ///             EmailProperty::From => "from".into(),
+    ///         }
+    ///     }
+    ///
+    ///     fn all_variants() -> &'static [Self] {
+    ///         &[EmailProperty::Subject, EmailProperty::From]
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(EmailProperty::all_variants().len(), 2);
+    /// assert!(EmailProperty::is_known("subject"));
+    /// assert!(!EmailProperty::is_known("size"));
+    /// ```
+    fn all_variants() -> &'static [Self]
+    where
+        Self: 'static,
+    {
+        &[]
+    }
+
+    /// Returns true if `value` parses as a property without a parent key.
+    fn is_known(value: &str) -> bool {
+        Self::try_parse(None, value).is_some()
+    }
 }
 
 pub trait Element: Clone + PartialEq + Eq + Hash + Debug + Sized {
@@ -33,6 +97,54 @@ pub trait Element: Clone + PartialEq + Eq + Hash + Debug + Sized {
 
     fn try_parse<P>(key: &Key<'_, Self::Property>, value: &str) -> Option<Self>;
     fn to_cow(&self) -> Cow<'static, str>;
+
+    /// Parses a string value stored under `key` with access to the object containing it, for
+    /// elements whose type depends on sibling fields. Defaults to [`Element::try_parse`].
+    ///
+    /// During deserialization this is called once the whole object has been read, and only
+    /// for its direct string values; strings nested in arrays or deeper objects are parsed
+    /// with `try_parse` as soon as they are read. The string values of `object` have not
+    /// been converted into elements yet, so siblings are always seen as `Value::Str`.
+    fn try_parse_in_object(
+        key: &Key<'_, Self::Property>,
+        value: &str,
+        object: &ObjectAsVec<'_, Self::Property, Self>,
+    ) -> Option<Self> {
+        let _ = object;
+        Self::try_parse::<Self::Property>(key, value)
+    }
+
+    /// Returns the full JSON representation of the element, used instead of its string form
+    /// when serializing with [`SerializeOptions::expand_elements`](crate::SerializeOptions).
+    /// Defaults to `None`, which keeps the string form.
+    fn to_value(&self) -> Option<Value<'static, Self::Property, Self>> {
+        None
+    }
+
+    /// Returns the JSON representation written by the `Serialize` implementation of
+    /// [`Value`]. Defaults to the string form from [`Element::to_cow`]. Elements that
+    /// serialize as an object should implement [`Element::try_parse_object`] to round-trip.
+    fn serialize_as(&self) -> ElementRepr<Self> {
+        ElementRepr::String(self.to_cow())
+    }
+
+    /// Parses an object stored under `key`, directly or inside an array, into an element.
+    /// This is the counterpart of an [`Element::serialize_as`] returning an object; objects
+    /// at the root of a document have no key and are never parsed. Defaults to `None`.
+    fn try_parse_object(
+        key: &Key<'_, Self::Property>,
+        object: &ObjectAsVec<'_, Self::Property, Self>,
+    ) -> Option<Self> {
+        let _ = (key, object);
+        None
+    }
+}
+
+/// The JSON representation of an element, see [`Element::serialize_as`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementRepr<E: Element> {
+    String(Cow<'static, str>),
+    Value(Value<'static, E::Property, E>),
 }
 
 impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
@@ -40,6 +152,55 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         Value::Object(ObjectAsVec::from(Vec::new()))
     }
 
+    /// Returns an empty Object.
+    ///
+    /// Together with [`Value::string`], [`Value::number`] and [`ObjectAsVec::with_key_value`]
+    /// values can be built fluently:
+    ///
+    /// ```
+    /// use jmap_tools::{Null, ObjectAsVec, Value};
+    ///
+    /// let mut mailbox = Value::<'_, Null, Null>::object();
+    /// mailbox
+    ///     .as_object_mut()
+    ///     .unwrap()
+    ///     .insert("name", Value::string("Inbox"));
+    ///
+    /// let email = Value::<'_, Null, Null>::Object(
+    ///     ObjectAsVec::new()
+    ///         .with_key_value("subject", Value::string("Hello"))
+    ///         .with_key_value("size", Value::number(1024u64))
+    ///         .with_key_value("keywords", Value::array())
+    ///         .with_key_value("replyTo", Value::null()),
+    /// );
+    ///
+    /// assert_eq!(mailbox.to_string(), r#"{"name":"Inbox"}"#);
+    /// assert_eq!(email.get("size").as_u64(), Some(1024));
+    /// ```
+    pub fn object() -> Self {
+        Value::Object(ObjectAsVec::new())
+    }
+
+    /// Returns an empty Array.
+    pub fn array() -> Self {
+        Value::Array(Vec::new())
+    }
+
+    /// Returns a String value, borrowing `value` when possible.
+    pub fn string(value: impl Into<Cow<'ctx, str>>) -> Self {
+        Value::Str(value.into())
+    }
+
+    /// Returns a Number value.
+    pub fn number(value: impl Into<Number>) -> Self {
+        Value::Number(value.into())
+    }
+
+    /// Returns `Value::Null`.
+    pub fn null() -> Self {
+        Value::Null
+    }
+
     pub fn new_boolean_set(set: impl IntoIterator<Item = (Key<'ctx, P>, bool)>) -> Self {
         let mut obj = Vec::new();
         for (key, value) in set {
@@ -52,12 +213,86 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         serde_json::from_str(json).map_err(|e| e.to_string())
     }
 
+    /// Parses JSON from a byte slice, validating UTF-8 as part of parsing. Strings without
+    /// escape sequences borrow from `json`, as with [`Value::parse_json`].
+    pub fn parse_json_bytes(json: &'ctx [u8]) -> Result<Self, String> {
+        serde_json::from_slice(json).map_err(|e| e.to_string())
+    }
+
     /// Returns a reference to the value corresponding to the key.
+    ///
+    /// Missing keys return a reference to `Value::Null`, which makes chained lookups
+    /// convenient but can't be told apart from a stored `null`. Use [`Value::get_opt`]
+    /// when that distinction matters.
     #[inline]
     pub fn get<I: Index<'ctx, P, E>>(&'ctx self, index: I) -> &'ctx Value<'ctx, P, E> {
         index.index_into(self).unwrap_or(&Value::Null)
     }
 
+    /// Returns a reference to the value corresponding to the key, or `None` if it is
+    /// not present.
+    ///
+    /// Unlike [`Value::get`], the result is only tied to the lifetime of the borrow,
+    /// so lookups can be chained with `?` as in `value.get_opt("a")?.get_opt("b")?`.
+    #[inline]
+    pub fn get_opt<'a, I: Index<'a, P, E>>(&'a self, index: I) -> Option<&'a Value<'a, P, E>> {
+        index.index_into(self)
+    }
+
+    /// Returns the value corresponding to the key converted to `T`, or `None` if the key is
+    /// missing or the value can't be converted, as in `value.get_as::<u64>("count")`.
+    #[inline]
+    pub fn get_as<'a, T: TryFrom<&'a Value<'a, P, E>>>(
+        &'a self,
+        index: impl Index<'a, P, E>,
+    ) -> Option<T> {
+        T::try_from(index.index_into(self)?).ok()
+    }
+
+    /// Returns a reference to the value corresponding to the key, or `default` if it is
+    /// missing or `null`.
+    ///
+    /// Unlike [`Value::get`], which falls back to `Value::Null`, the fallback is chosen
+    /// by the caller.
+    #[inline]
+    pub fn get_or<'a, I: Index<'a, P, E>>(
+        &'a self,
+        index: I,
+        default: &'a Value<'a, P, E>,
+    ) -> &'a Value<'a, P, E> {
+        match index.index_into(self) {
+            Some(Value::Null) | None => default,
+            Some(value) => value,
+        }
+    }
+
+    /// Returns `self` unless it is `Value::Null`, in which case `default` is returned.
+    #[inline]
+    pub fn or(self, default: Value<'ctx, P, E>) -> Value<'ctx, P, E> {
+        match self {
+            Value::Null => default,
+            value => value,
+        }
+    }
+
+    /// Returns `self` unless it is `Value::Null`, in which case the result of `f` is
+    /// returned.
+    #[inline]
+    pub fn or_else<F: FnOnce() -> Value<'ctx, P, E>>(self, f: F) -> Value<'ctx, P, E> {
+        match self {
+            Value::Null => f(),
+            value => value,
+        }
+    }
+
+    /// Parses `pointer` as a JSON Pointer and returns the value it resolves to.
+    ///
+    /// Only concrete paths are supported: if the pointer contains a wildcard,
+    /// `None` is returned.
+    pub fn get_str_pointer(&self, pointer: &str) -> Option<&Value<'ctx, P, E>> {
+        self.resolve_pointer(&JsonPointer::<P>::parse(pointer))
+    }
+
     pub fn is_object_and_contains_key(&self, key: &Key<'_, P>) -> bool {
         match self {
             Value::Object(obj) => obj.contains_key(key),
@@ -110,6 +345,34 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         matches!(self, Value::Str(_))
     }
 
+    /// Returns true if `Value` is Value::Element.
+    pub fn is_element(&self) -> bool {
+        matches!(self, Value::Element(_))
+    }
+
+    /// Returns true if the Value is `null`, an empty Array, an empty Object or an empty
+    /// String. Numbers, booleans and elements are never considered empty.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::Str(s) => s.is_empty(),
+            Value::Array(arr) => arr.is_empty(),
+            Value::Object(obj) => obj.is_empty(),
+            Value::Bool(_) | Value::Number(_) | Value::Element(_) => false,
+        }
+    }
+
+    /// Returns the number of items in an Array, entries in an Object or characters in a
+    /// String. Returns None for `null`, numbers, booleans and elements.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Value::Str(s) => Some(s.chars().count()),
+            Value::Array(arr) => Some(arr.len()),
+            Value::Object(obj) => Some(obj.len()),
+            Value::Null | Value::Bool(_) | Value::Number(_) | Value::Element(_) => None,
+        }
+    }
+
     /// Returns true if the Value is an integer between i64::MIN and i64::MAX.
     /// For any Value on which is_i64 returns true, as_i64 is guaranteed to return the integer
     /// value.
@@ -184,6 +447,34 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         }
     }
 
+    /// If the Value is an Element, returns the associated Element. Returns None otherwise.
+    ///
+    /// ```
+    /// use jmap_tools::{Element, Key, Null, Value};
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    /// enum Keyword {
+    ///     Seen,
+    /// }
+    ///
+    /// impl Element for Keyword {
+    ///     type Property = Null;
+    ///
+    ///     fn try_parse<P>(_: &Key<'_, Null>, value: &str) -> Option<Self> {
+    ///         (value == "$seen").then_some(Keyword::Seen)
+    ///     }
+    ///
+    ///     fn to_cow(&self) -> Cow<'static, str> {
+    ///         "$seen".into()
+    ///     }
+    /// }
+    ///
+    /// let value = Value::<'_, Null, Keyword>::parse_json(r#"{"keyword": "$seen"}"#).unwrap();
+    /// let keyword = value.get("keyword");
+    /// assert!(keyword.is_element());
+    /// assert_eq!(keyword.as_element(), Some(&Keyword::Seen));
+    /// ```
     pub fn as_element(&self) -> Option<&E> {
         match self {
             Value::Element(element) => Some(element),
@@ -198,6 +489,49 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         }
     }
 
+    /// Returns the entries of an object as a mutable slice, allowing values to be modified
+    /// and entries to be reordered in place, for example with `sort_by`. Returns None if the
+    /// Value is not an Object.
+    ///
+    /// Keys can be overwritten through the slice, but doing so may introduce duplicates;
+    /// adding or removing entries has to go through the [`ObjectAsVec`] methods.
+    pub fn entries_mut(&mut self) -> Option<&mut [ObjectEntry<'ctx, P, E>]> {
+        match self {
+            Value::Object(obj) => Some(obj.0.as_mut_slice()),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the object, replacing the value with an empty object
+    /// first if it is `Null`.
+    ///
+    /// ## Panics
+    /// Panics if the value is neither `Null` nor an object.
+    pub fn as_object_or_insert(&mut self) -> &mut ObjectAsVec<'ctx, P, E> {
+        if self.is_null() {
+            *self = Value::Object(ObjectAsVec::new());
+        }
+        match self {
+            Value::Object(obj) => obj,
+            _ => panic!("as_object_or_insert called on a non-object value"),
+        }
+    }
+
+    /// Returns a mutable reference to the array, replacing the value with an empty array
+    /// first if it is `Null`.
+    ///
+    /// ## Panics
+    /// Panics if the value is neither `Null` nor an array.
+    pub fn as_array_or_insert(&mut self) -> &mut Vec<Value<'ctx, P, E>> {
+        if self.is_null() {
+            *self = Value::Array(Vec::new());
+        }
+        match self {
+            Value::Array(arr) => arr,
+            _ => panic!("as_array_or_insert called on a non-array value"),
+        }
+    }
+
     pub fn into_object(self) -> Option<ObjectAsVec<'ctx, P, E>> {
         match self {
             Value::Object(obj) => Some(obj),
@@ -205,6 +539,57 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         }
     }
 
+    /// If the Value is an Object with exactly one entry, returns its key and value. Returns
+    /// None otherwise, including for objects with more than one entry.
+    pub fn as_single_entry(&self) -> Option<(&Key<'ctx, P>, &Value<'ctx, P, E>)> {
+        match self {
+            Value::Object(obj) if obj.len() == 1 => obj.first(),
+            _ => None,
+        }
+    }
+
+    /// Consumes the Value and returns the key and value of its only entry, if it is an Object
+    /// with exactly one entry.
+    pub fn into_single_entry(self) -> Option<(Key<'ctx, P>, Value<'ctx, P, E>)> {
+        match self {
+            Value::Object(obj) if obj.len() == 1 => obj.into_vec().pop(),
+            _ => None,
+        }
+    }
+
+    /// Returns a mutable reference to the array stored under `key`, inserting an empty array
+    /// if the key is missing or its value is `null`.
+    ///
+    /// Returns `None` if `self` is not an object or the existing value is neither an array
+    /// nor `null`.
+    pub fn ensure_array(&mut self, key: &Key<'ctx, P>) -> Option<&mut Vec<Value<'ctx, P, E>>> {
+        let value = self
+            .as_object_mut()?
+            .insert_or_get_mut(key.clone(), Value::Array(Vec::new()));
+        if value.is_null() {
+            *value = Value::Array(Vec::new());
+        }
+        match value {
+            Value::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    /// Appends `value` to the array stored under `key`, creating the array if needed, see
+    /// [`Value::ensure_array`]. Returns `false` if the value could not be appended.
+    pub fn push_to_array(&mut self, key: &Key<'ctx, P>, value: Value<'ctx, P, E>) -> bool {
+        if let Some(arr) = self.ensure_array(key) {
+            arr.push(value);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Converts the value into one that does not borrow from the input.
+    ///
+    /// Array and object buffers are collected in place, so their allocations are
+    /// reused and only borrowed strings and keys are copied.
     pub fn into_owned(self) -> Value<'static, P, E> {
         match self {
             Value::Null => Value::Null,
@@ -226,167 +611,1161 @@ impl<'ctx, P: Property, E: Element<Property = P>> Value<'ctx, P, E> {
         }
     }
 
-    pub fn into_expanded_object(self) -> impl Iterator<Item = (Key<'ctx, P>, Value<'ctx, P, E>)> {
-        self.into_object()
-            .map(|obj| obj.into_vec())
-            .unwrap_or_default()
-            .into_iter()
-    }
-
-    pub fn into_expanded_boolean_set(self) -> impl Iterator<Item = Key<'ctx, P>> {
-        self.into_object()
-            .map(|obj| obj.into_vec())
-            .unwrap_or_default()
-            .into_iter()
-            .filter_map(|(key, value)| value.as_bool().filter(|&b| b).map(|_| key))
-    }
-
-    pub fn into_element(self) -> Option<E> {
+    /// Returns a copy of the value that does not borrow from the input, without consuming
+    /// it. This makes the same allocations as `clone().into_owned()`, one per string, key
+    /// and container, but walks the value once instead of twice.
+    pub fn to_owned_value(&self) -> Value<'static, P, E> {
         match self {
-            Value::Element(element) => Some(element),
-            _ => None,
+            Value::Null => Value::Null,
+            Value::Bool(b) => Value::Bool(*b),
+            Value::Number(n) => Value::Number(n.to_owned()),
+            Value::Element(e) => Value::Element(e.clone()),
+            Value::Str(s) => Value::Str(Cow::Owned(s.as_ref().to_owned())),
+            Value::Array(arr) => Value::Array(arr.iter().map(|v| v.to_owned_value()).collect()),
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned_value()))
+                    .collect(),
+            ),
         }
     }
 
-    /// If the Value is a Boolean, returns the associated bool. Returns None otherwise.
-    pub fn as_bool(&self) -> Option<bool> {
+    /// Converts all borrowed strings and keys into owned ones in place, leaving
+    /// containers and already owned data untouched. It allocates only for the borrowed
+    /// strings and keys, like [`Value::into_owned`], but does not move or rebuild any
+    /// array or object. Entries that are already owned are visited but never moved.
+    pub fn make_owned(&mut self) {
         match self {
-            Value::Bool(b) => Some(*b),
-            _ => None,
+            Value::Str(s) => {
+                if let Cow::Borrowed(b) = s {
+                    *s = Cow::Owned(b.to_string());
+                }
+            }
+            Value::Array(arr) => {
+                for v in arr {
+                    v.make_owned();
+                }
+            }
+            Value::Object(obj) => {
+                for (k, v) in obj.iter_mut() {
+                    if let Key::Borrowed(b) = k {
+                        *k = Key::Owned(b.to_string());
+                    }
+                    v.make_owned();
+                }
+            }
+            _ => {}
         }
     }
 
-    /// If the Value is a String, returns the associated str. Returns None otherwise.
-    pub fn as_str(&self) -> Option<Cow<'_, str>> {
-        match self {
-            Value::Str(text) => Some(text.as_ref().into()),
-            Value::Element(element) => Some(element.to_cow()),
-            _ => None,
+    /// Merges `other` into this value. When both values are objects their entries are merged
+    /// recursively, otherwise this value is replaced by `other`.
+    pub fn deep_merge(&mut self, other: Value<'ctx, P, E>) {
+        match (self, other) {
+            (Value::Object(obj), Value::Object(other)) => {
+                for (key, value) in other.into_vec() {
+                    if let Some(existing) = obj.get_mut(&key) {
+                        existing.deep_merge(value);
+                    } else {
+                        obj.insert_unchecked(key, value);
+                    }
+                }
+            }
+            (this, other) => *this = other,
         }
     }
 
-    pub fn into_string(self) -> Option<Cow<'ctx, str>> {
-        match self {
-            Value::Str(text) => Some(text),
-            Value::Element(element) => Some(element.to_cow()),
-            _ => None,
+    /// Merges two arrays of objects, matching elements by the value of their `key` field.
+    ///
+    /// Matched objects are merged with [`Value::deep_merge`], while elements of `other` that are
+    /// not objects, lack the `key` field or have no match are appended. If either value is not an
+    /// array, `other` is deep merged into this value instead.
+    pub fn merge_arrays_by_key(&mut self, other: Value<'ctx, P, E>, key: &Key<'_, P>) {
+        match (self, other) {
+            (Value::Array(arr), Value::Array(other)) => {
+                for item in other {
+                    let pos = item.as_object_and_get(key).and_then(|id| {
+                        arr.iter()
+                            .position(|existing| existing.as_object_and_get(key) == Some(id))
+                    });
+
+                    if let Some(pos) = pos {
+                        arr[pos].deep_merge(item);
+                    } else {
+                        arr.push(item);
+                    }
+                }
+            }
+            (this, other) => this.deep_merge(other),
         }
     }
 
-    pub fn into_owned_string(self) -> Option<String> {
-        match self {
-            Value::Str(text) => Some(text.into_owned()),
-            Value::Element(element) => Some(element.to_cow().into_owned()),
-            _ => None,
+    /// Merges `patch` into this value using the given strategy.
+    pub fn merge(&mut self, patch: Value<'ctx, P, E>, strategy: &MergeStrategy<'_, P>) {
+        match strategy {
+            MergeStrategy::Replace => *self = patch,
+            MergeStrategy::Deep => self.deep_merge(patch),
+            MergeStrategy::ArraysByKey(key) => self.merge_arrays_by_key(patch, key),
         }
     }
 
-    /// If the Value is an integer, represent it as i64 if possible. Returns None otherwise.
-    pub fn as_i64(&self) -> Option<i64> {
-        match self {
-            Value::Number(n) => n.as_i64(),
-            _ => None,
+    /// Merges `patch` into the value addressed by `pointer`, creating the missing parent
+    /// objects along the way as [`Value::get_or_create`] does, and merging into `null` when
+    /// the target itself is missing.
+    ///
+    /// Returns false, leaving the value untouched, if the path cannot be created: the pointer
+    /// contains wildcards, indexes an array out of bounds or traverses a scalar.
+    pub fn merge_at(
+        &mut self,
+        pointer: JsonPointerIter<'_, P>,
+        patch: Value<'ctx, P, E>,
+        strategy: MergeStrategy<'_, P>,
+    ) -> bool {
+        if pointer.clone().any(|item| {
+            matches!(
+                item,
+                JsonPointerItem::Wildcard | JsonPointerItem::RecursiveWildcard
+            )
+        }) {
+            return false;
+        }
+
+        match self.get_or_create(pointer) {
+            Some(target) => {
+                target.merge(patch, &strategy);
+                true
+            }
+            None => false,
         }
     }
 
-    /// If the Value is an integer, represent it as u64 if possible. Returns None otherwise.
-    pub fn as_u64(&self) -> Option<u64> {
+    /// Applies `f` to every node of the tree, bottom-up, and returns the transformed value.
+    ///
+    /// Nodes are visited in post-order: the children of an array or object are transformed
+    /// first and the container, holding the transformed children, is passed to `f` last.
+    pub fn map_values<F>(self, mut f: F) -> Value<'ctx, P, E>
+    where
+        F: FnMut(Value<'ctx, P, E>) -> Value<'ctx, P, E>,
+    {
+        self.map_values_inner(&mut f)
+    }
+
+    fn map_values_inner<F>(self, f: &mut F) -> Value<'ctx, P, E>
+    where
+        F: FnMut(Value<'ctx, P, E>) -> Value<'ctx, P, E>,
+    {
+        let value = match self {
+            Value::Array(arr) => {
+                Value::Array(arr.into_iter().map(|v| v.map_values_inner(f)).collect())
+            }
+            Value::Object(obj) => Value::Object(
+                obj.into_vec()
+                    .into_iter()
+                    .map(|(k, v)| (k, v.map_values_inner(f)))
+                    .collect(),
+            ),
+            value => value,
+        };
+        f(value)
+    }
+
+    /// Calls `f` on every node of the tree.
+    ///
+    /// Nodes are visited in post-order: the children of an array or object are visited
+    /// before the container itself.
+    pub fn visit<F>(&self, mut f: F)
+    where
+        F: FnMut(&Value<'ctx, P, E>),
+    {
+        self.visit_inner(&mut f);
+    }
+
+    fn visit_inner<F>(&self, f: &mut F)
+    where
+        F: FnMut(&Value<'ctx, P, E>),
+    {
         match self {
-            Value::Number(n) => n.as_u64(),
-            _ => None,
+            Value::Array(arr) => {
+                for v in arr {
+                    v.visit_inner(f);
+                }
+            }
+            Value::Object(obj) => {
+                for v in obj.values() {
+                    v.visit_inner(f);
+                }
+            }
+            _ => {}
         }
+        f(self);
     }
 
-    /// If the Value is a number, represent it as f64 if possible. Returns None otherwise.
-    pub fn as_f64(&self) -> Option<f64> {
+    /// HTML-escapes every string of the tree in place, replacing `&`, `<`, `>`, `"` and `'`
+    /// with character references. Only `Value::Str` leaves are changed: keys, elements,
+    /// numbers and booleans are left alone, and strings without any of these characters
+    /// keep borrowing from the input.
+    pub fn escape_strings_for_html(&mut self) {
         match self {
-            Value::Number(n) => n.as_f64(),
-            _ => None,
+            Value::Str(text) => {
+                if let Some(escaped) = escape_html(text) {
+                    *text = Cow::Owned(escaped);
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    value.escape_strings_for_html();
+                }
+            }
+            Value::Object(map) => {
+                for (_, value) in map.iter_mut() {
+                    value.escape_strings_for_html();
+                }
+            }
+            _ => {}
         }
     }
-}
 
-impl<P: Property, E: Element> From<bool> for Value<'_, P, E> {
-    fn from(val: bool) -> Self {
-        Value::Bool(val)
+    /// Returns a copy of the value with every string HTML-escaped, see
+    /// [`Value::escape_strings_for_html`].
+    pub fn to_html_escaped(&self) -> Value<'ctx, P, E> {
+        let mut value = self.clone();
+        value.escape_strings_for_html();
+        value
+    }
+
+    /// Calls `f` on every node of the tree with its location and a mutable reference, so
+    /// values can be rewritten depending on where they are. The root is passed as `[Root]`.
+    ///
+    /// Nodes are visited depth-first in pre-order: `f` is called on a container before its
+    /// children, and the traversal descends into the children as left by `f`.
+    pub fn walk_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&JsonPointer<P>, &mut Value<'ctx, P, E>),
+    {
+        let mut path = JsonPointer(vec![JsonPointerItem::Root]);
+        f(&path, self);
+        path.0.clear();
+        self.walk_mut_children(&mut path, &mut f);
+    }
+
+    fn walk_mut_children<F>(&mut self, path: &mut JsonPointer<P>, f: &mut F)
+    where
+        F: FnMut(&JsonPointer<P>, &mut Value<'ctx, P, E>),
+    {
+        match self {
+            Value::Array(arr) => {
+                for (idx, v) in arr.iter_mut().enumerate() {
+                    path.0.push(JsonPointerItem::Number(idx as u64));
+                    f(path, v);
+                    v.walk_mut_children(path, f);
+                    path.0.pop();
+                }
+            }
+            Value::Object(obj) => {
+                for (k, v) in obj.iter_mut() {
+                    path.0.push(k.to_pointer_item());
+                    f(path, v);
+                    v.walk_mut_children(path, f);
+                    path.0.pop();
+                }
+            }
+            _ => {}
+        }
     }
-}
 
-impl<'a, P: Property, E: Element> From<&'a str> for Value<'a, P, E> {
-    fn from(val: &'a str) -> Self {
-        Value::Str(Cow::Borrowed(val))
-    }
-}
+    /// Returns the maximum nesting depth of the value. Scalars have a depth of 0,
+    /// while arrays and objects add one level to the depth of their deepest child.
+    ///
+    /// The tree is traversed iteratively so deeply nested input cannot overflow the stack.
+    pub fn depth(&self) -> usize {
+        let mut max_depth = 0;
+        let mut stack = vec![(self, 0)];
 
-impl<P: Property, E: Element> From<String> for Value<'_, P, E> {
-    fn from(val: String) -> Self {
-        Value::Str(Cow::Owned(val))
-    }
-}
+        while let Some((value, depth)) = stack.pop() {
+            match value {
+                Value::Array(arr) => {
+                    max_depth = max_depth.max(depth + 1);
+                    stack.extend(arr.iter().map(|v| (v, depth + 1)));
+                }
+                Value::Object(obj) => {
+                    max_depth = max_depth.max(depth + 1);
+                    stack.extend(obj.values().map(|v| (v, depth + 1)));
+                }
+                _ => {}
+            }
+        }
 
-impl<'x, P: Property, E: Element> From<Cow<'x, str>> for Value<'x, P, E> {
-    fn from(val: Cow<'x, str>) -> Self {
-        Value::Str(val)
+        max_depth
     }
-}
 
-impl<'a, P: Property, E: Element, T: Into<Value<'a, P, E>>> From<Vec<T>> for Value<'a, P, E> {
-    fn from(val: Vec<T>) -> Self {
-        Value::Array(val.into_iter().map(Into::into).collect())
-    }
-}
+    /// Returns the total number of nodes in the value, counting every scalar and container.
+    ///
+    /// The tree is traversed iteratively so deeply nested input cannot overflow the stack.
+    pub fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        let mut stack = vec![self];
 
-impl<'x, P: Property, E: Element, T: Into<Value<'x, P, E>>> From<Option<T>> for Value<'x, P, E> {
-    fn from(val: Option<T>) -> Self {
-        match val {
-            Some(v) => v.into(),
-            None => Value::Null,
+        while let Some(value) = stack.pop() {
+            count += 1;
+            match value {
+                Value::Array(arr) => stack.extend(arr.iter()),
+                Value::Object(obj) => stack.extend(obj.values()),
+                _ => {}
+            }
         }
+
+        count
     }
-}
 
-impl<'x, P: Property, E: Element> From<E> for Value<'x, P, E> {
-    fn from(element: E) -> Self {
-        Value::Element(element)
+    /// Flattens the value into a list of human-readable paths such as `a.b[0].c` and clones
+    /// of their scalar leaves, using the default [`FlattenOptions`].
+    pub fn flatten(&self) -> Vec<(String, Value<'ctx, P, E>)> {
+        self.flatten_with(&FlattenOptions::default())
     }
-}
 
-impl<'a, P: Property, E: Element, T: Clone + Into<Value<'a, P, E>>> From<&[T]> for Value<'a, P, E> {
-    fn from(val: &[T]) -> Self {
-        Value::Array(val.iter().map(Clone::clone).map(Into::into).collect())
+    /// Flattens the value into a list of paths and cloned scalar leaves, in document order.
+    /// A scalar root is returned with an empty path.
+    pub fn flatten_with(&self, options: &FlattenOptions) -> Vec<(String, Value<'ctx, P, E>)> {
+        let mut results = Vec::new();
+        self.flatten_inner(options, &mut String::new(), &mut results);
+        results
     }
-}
 
-impl<P: Property, E: Element> Debug for Value<'_, P, E> {
-    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn flatten_inner(
+        &self,
+        options: &FlattenOptions,
+        path: &mut String,
+        results: &mut Vec<(String, Value<'ctx, P, E>)>,
+    ) {
+        let len = path.len();
         match self {
-            Value::Null => formatter.write_str("Null"),
-            Value::Bool(boolean) => write!(formatter, "Bool({})", boolean),
-            Value::Number(number) => match number.n {
-                N::PosInt(n) => write!(formatter, "Number({:?})", n),
-                N::NegInt(n) => write!(formatter, "Number({:?})", n),
-                N::Float(n) => write!(formatter, "Number({:?})", n),
-            },
-            Value::Str(string) => write!(formatter, "Str({:?})", string),
-            Value::Array(vec) => {
-                formatter.write_str("Array ")?;
-                Debug::fmt(vec, formatter)
+            Value::Array(arr) if !arr.is_empty() => {
+                for (idx, v) in arr.iter().enumerate() {
+                    match options.array_notation {
+                        ArrayNotation::Brackets => {
+                            path.push('[');
+                            path.push_str(&idx.to_string());
+                            path.push(']');
+                        }
+                        ArrayNotation::Separator => {
+                            if len > 0 {
+                                path.push_str(&options.separator);
+                            }
+                            path.push_str(&idx.to_string());
+                        }
+                    }
+                    v.flatten_inner(options, path, results);
+                    path.truncate(len);
+                }
             }
-            Value::Object(map) => {
-                formatter.write_str("Object ")?;
-                Debug::fmt(map, formatter)
+            Value::Object(obj) if !obj.is_empty() => {
+                for (k, v) in obj.iter() {
+                    if len > 0 {
+                        path.push_str(&options.separator);
+                    }
+                    path.push_str(&k.to_string());
+                    v.flatten_inner(options, path, results);
+                    path.truncate(len);
+                }
             }
-            Value::Element(element) => write!(formatter, "Element({})", element.to_cow()),
+            Value::Array(_) | Value::Object(_) if !options.keep_empty => {}
+            _ => results.push((path.clone(), self.clone())),
         }
     }
-}
-
-// We just convert to serde_json::Value to Display
-impl<P: Property, E: Element> Display for Value<'_, P, E> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", serde_json::Value::from(self.clone()))
-    }
-}
+
+    /// Recursively shortens every string longer than `max_len` characters to its first
+    /// `max_len` characters followed by a `…` marker, for example to produce log-safe output.
+    ///
+    /// Elements and object keys are left untouched.
+    pub fn truncate_strings(&mut self, max_len: usize) {
+        match self {
+            Value::Str(text) => {
+                if let Some((pos, _)) = text.char_indices().nth(max_len) {
+                    let mut truncated = String::with_capacity(pos + '…'.len_utf8());
+                    truncated.push_str(&text[..pos]);
+                    truncated.push('…');
+                    *text = Cow::Owned(truncated);
+                }
+            }
+            Value::Array(arr) => {
+                for v in arr {
+                    v.truncate_strings(max_len);
+                }
+            }
+            Value::Object(obj) => {
+                for (_, v) in obj.iter_mut() {
+                    v.truncate_strings(max_len);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns a copy of the value with long strings truncated, see
+    /// [`Value::truncate_strings`].
+    pub fn to_truncated(&self, max_len: usize) -> Self {
+        let mut value = self.clone();
+        value.truncate_strings(max_len);
+        value
+    }
+
+    /// Projects an object onto the requested properties, in the requested order. Keys that
+    /// are absent from the object are skipped and duplicate keys are only included once.
+    /// `None` requests all properties, as when a JMAP `get` omits `properties`.
+    ///
+    /// Non-object values are returned as a clone.
+    pub fn pick(&self, keys: Option<&[Key<'_, P>]>) -> Self {
+        match (self, keys) {
+            (Value::Object(obj), Some(keys)) => {
+                let mut picked = ObjectAsVec::with_capacity(keys.len());
+                for key in keys {
+                    if let Some((key, value)) = obj.iter().find(|(k, _)| *k == key)
+                        && !picked.contains_key(key)
+                    {
+                        picked.insert_unchecked(key.clone(), value.clone());
+                    }
+                }
+                Value::Object(picked)
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Returns a copy of an object without the listed properties, preserving the order of the
+    /// remaining ones. Non-object values are returned as a clone.
+    pub fn omit(&self, keys: &[Key<'_, P>]) -> Self {
+        match self {
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .filter(|(k, _)| !keys.iter().any(|key| key == *k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+            _ => self.clone(),
+        }
+    }
+
+    /// Recursively removes object entries whose value is `Value::Null`.
+    ///
+    /// Nulls inside arrays are left in place since they are meaningful positions.
+    pub fn strip_nulls(&mut self) {
+        match self {
+            Value::Object(obj) => obj.retain(|_, v| {
+                if v.is_null() {
+                    false
+                } else {
+                    v.strip_nulls();
+                    true
+                }
+            }),
+            Value::Array(arr) => {
+                for v in arr {
+                    v.strip_nulls();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Sorts the entries of an Object by key using the supplied comparator, leaving nested
+    /// values untouched. Does nothing for other values.
+    pub fn sort_keys_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&Key<'ctx, P>, &Key<'ctx, P>) -> Ordering,
+    {
+        if let Value::Object(obj) = self {
+            obj.sort_keys_by(f);
+        }
+    }
+
+    /// Sorts the entries of every Object in the value by key using the supplied comparator,
+    /// including objects nested in arrays.
+    pub fn sort_keys_by_recursive<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Key<'ctx, P>, &Key<'ctx, P>) -> Ordering,
+    {
+        self.sort_keys_by_recursive_inner(&mut f);
+    }
+
+    fn sort_keys_by_recursive_inner<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&Key<'ctx, P>, &Key<'ctx, P>) -> Ordering,
+    {
+        match self {
+            Value::Object(obj) => {
+                obj.sort_keys_by(&mut *f);
+                for (_, v) in obj.iter_mut() {
+                    v.sort_keys_by_recursive_inner(f);
+                }
+            }
+            Value::Array(arr) => {
+                for v in arr {
+                    v.sort_keys_by_recursive_inner(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Retains only the array elements for which the predicate returns `true`, preserving
+    /// their order. Returns false if the value is not an array.
+    pub fn retain_array<F>(&mut self, f: F) -> bool
+    where
+        F: FnMut(&Value<'ctx, P, E>) -> bool,
+    {
+        if let Value::Array(arr) = self {
+            arr.retain(f);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Applies [`Value::retain_array`] to every array in the tree, including arrays nested in
+    /// objects. Each array is filtered before its remaining elements are visited.
+    pub fn retain_array_recursive<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Value<'ctx, P, E>) -> bool,
+    {
+        self.retain_array_recursive_inner(&mut f);
+    }
+
+    fn retain_array_recursive_inner<F>(&mut self, f: &mut F)
+    where
+        F: FnMut(&Value<'ctx, P, E>) -> bool,
+    {
+        match self {
+            Value::Object(obj) => {
+                for (_, v) in obj.iter_mut() {
+                    v.retain_array_recursive_inner(f);
+                }
+            }
+            Value::Array(arr) => {
+                arr.retain(|v| f(v));
+                for v in arr {
+                    v.retain_array_recursive_inner(f);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Consumes the value and returns it with all null object entries removed,
+    /// see [`Value::strip_nulls`].
+    pub fn without_nulls(mut self) -> Self {
+        self.strip_nulls();
+        self
+    }
+
+    pub fn into_expanded_object(self) -> impl Iterator<Item = (Key<'ctx, P>, Value<'ctx, P, E>)> {
+        self.into_object()
+            .map(|obj| obj.into_vec())
+            .unwrap_or_default()
+            .into_iter()
+    }
+
+    pub fn into_expanded_boolean_set(self) -> impl Iterator<Item = Key<'ctx, P>> {
+        self.into_object()
+            .map(|obj| obj.into_vec())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(key, value)| value.as_bool().filter(|&b| b).map(|_| key))
+    }
+
+    pub fn into_element(self) -> Option<E> {
+        match self {
+            Value::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// If the Value is a Boolean, returns the associated bool. Returns None otherwise.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// If the Value is a String, returns the associated str. Returns None otherwise.
+    pub fn as_str(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Str(text) => Some(text.as_ref().into()),
+            Value::Element(element) => Some(element.to_cow()),
+            _ => None,
+        }
+    }
+
+    /// If the Value is a String or an Element that can be represented without allocating,
+    /// returns it as a borrowed str. Returns None otherwise, including for Elements whose
+    /// string form has to be built as an owned value.
+    pub fn as_borrowed_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(text) => Some(text.as_ref()),
+            Value::Element(element) => match element.to_cow() {
+                Cow::Borrowed(text) => Some(text),
+                Cow::Owned(_) => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn into_string(self) -> Option<Cow<'ctx, str>> {
+        match self {
+            Value::Str(text) => Some(text),
+            Value::Element(element) => Some(element.to_cow()),
+            _ => None,
+        }
+    }
+
+    pub fn into_owned_string(self) -> Option<String> {
+        match self {
+            Value::Str(text) => Some(text.into_owned()),
+            Value::Element(element) => Some(element.to_cow().into_owned()),
+            _ => None,
+        }
+    }
+
+    /// If the Value is an integer, represent it as i64 if possible. Returns None otherwise.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Number(n) => n.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// If the Value is an integer, represent it as u64 if possible. Returns None otherwise.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Number(n) => n.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// If the Value is a number, represent it as f64 if possible. Returns None otherwise.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => n.as_f64(),
+            _ => None,
+        }
+    }
+
+    /// If the Value is an Array of unsigned integers, returns them as a Vec. Returns None if
+    /// the Value is not an Array or if any item is not an unsigned integer.
+    ///
+    /// Use [`Value::collect_u64_vec`] to skip mismatched items instead.
+    pub fn as_u64_vec(&self) -> Option<Vec<u64>> {
+        self.as_array()?.iter().map(Value::as_u64).collect()
+    }
+
+    /// If the Value is an Array of strings or elements, returns them as a Vec. Returns None
+    /// if the Value is not an Array or if any item is not a string.
+    ///
+    /// Use [`Value::collect_str_vec`] to skip mismatched items instead.
+    pub fn as_str_vec(&self) -> Option<Vec<Cow<'_, str>>> {
+        self.as_array()?.iter().map(Value::as_str).collect()
+    }
+
+    /// If the Value is an Array of booleans, returns them as a Vec. Returns None if the Value
+    /// is not an Array or if any item is not a boolean.
+    ///
+    /// Use [`Value::collect_bool_vec`] to skip mismatched items instead.
+    pub fn as_bool_vec(&self) -> Option<Vec<bool>> {
+        self.as_array()?.iter().map(Value::as_bool).collect()
+    }
+
+    /// Returns the unsigned integer items of an Array, skipping items of any other type.
+    /// Returns an empty Vec if the Value is not an Array.
+    pub fn collect_u64_vec(&self) -> Vec<u64> {
+        self.array_items().filter_map(Value::as_u64).collect()
+    }
+
+    /// Returns the string and element items of an Array, skipping items of any other type.
+    /// Returns an empty Vec if the Value is not an Array.
+    pub fn collect_str_vec(&self) -> Vec<Cow<'_, str>> {
+        self.array_items().filter_map(Value::as_str).collect()
+    }
+
+    /// Returns the boolean items of an Array, skipping items of any other type.
+    /// Returns an empty Vec if the Value is not an Array.
+    pub fn collect_bool_vec(&self) -> Vec<bool> {
+        self.array_items().filter_map(Value::as_bool).collect()
+    }
+
+    fn array_items(&self) -> impl Iterator<Item = &Value<'ctx, P, E>> {
+        self.as_array().unwrap_or_default().iter()
+    }
+}
+
+impl<'ctx, P: Property, E: Element> Value<'ctx, P, E> {
+    /// Returns a string value holding `bytes` encoded as standard base64 with padding, the
+    /// representation JMAP uses for inline binary data.
+    ///
+    /// Binary data is kept in the `Str` variant rather than in a dedicated variant so that
+    /// matching on `Value` remains exhaustive for all users; decode it on access with
+    /// [`Value::base64_decode`].
+    pub fn base64_encode_str(bytes: &[u8]) -> Self {
+        Value::Str(Cow::Owned(base64::encode(bytes)))
+    }
+
+    /// If the Value is a String holding valid base64, returns the decoded bytes. Returns None
+    /// otherwise. Padding is optional.
+    pub fn base64_decode(&self) -> Option<Vec<u8>> {
+        match self {
+            Value::Str(text) => base64::decode(text),
+            _ => None,
+        }
+    }
+
+    /// Validates the value against `schema`, see [`Schema::validate`].
+    pub fn validate_against(&self, schema: &Schema<P>) -> Result<(), Vec<ValidationError<P>>> {
+        schema.validate(self)
+    }
+
+    /// Leniently converts the value to a string:
+    ///
+    /// - Strings and elements are returned as is.
+    /// - Booleans become `"true"` or `"false"` and numbers use their JSON representation.
+    /// - `null`, arrays and objects become an empty string.
+    ///
+    /// Use [`Value::as_str`] for a strict conversion.
+    pub fn coerce_to_string(&self) -> Cow<'_, str> {
+        match self {
+            Value::Str(text) => Cow::Borrowed(text.as_ref()),
+            Value::Element(element) => element.to_cow(),
+            Value::Bool(true) => Cow::Borrowed("true"),
+            Value::Bool(false) => Cow::Borrowed("false"),
+            Value::Number(n) => Cow::Owned(n.to_string()),
+            Value::Null | Value::Array(_) | Value::Object(_) => Cow::Borrowed(""),
+        }
+    }
+
+    /// Leniently converts the value to a number:
+    ///
+    /// - Numbers are returned as is.
+    /// - Booleans become `1` or `0`.
+    /// - Strings and elements are trimmed and parsed as an integer, falling back to a finite
+    ///   float. Empty or unparsable strings return `None`.
+    /// - `null`, arrays and objects return `None`.
+    ///
+    /// Use [`Value::as_f64`] and the other numeric accessors for a strict conversion.
+    pub fn coerce_to_number(&self) -> Option<Number> {
+        let text = match self {
+            Value::Number(n) => return Some(*n),
+            Value::Bool(b) => return Some(N::PosInt(*b as u64).into()),
+            Value::Str(text) => text.as_ref().into(),
+            Value::Element(element) => element.to_cow(),
+            Value::Null | Value::Array(_) | Value::Object(_) => return None,
+        };
+        let text = text.trim();
+
+        if let Ok(n) = text.parse::<u64>() {
+            Some(N::PosInt(n).into())
+        } else if let Ok(n) = text.parse::<i64>() {
+            Some(
+                if n < 0 {
+                    N::NegInt(n)
+                } else {
+                    N::PosInt(n as u64)
+                }
+                .into(),
+            )
+        } else {
+            text.parse::<f64>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .map(|n| N::Float(n).into())
+        }
+    }
+
+    pub(crate) fn resolve_pointer(&self, pointer: &JsonPointer<P>) -> Option<&Value<'ctx, P, E>> {
+        let mut value = self;
+
+        for item in pointer.as_slice() {
+            value = match (item, value) {
+                (JsonPointerItem::Root, _) => value,
+                (JsonPointerItem::Key(key), Value::Object(obj)) => obj.get(key)?,
+                (
+                    JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_),
+                    Value::Array(arr),
+                ) => arr.get(item.array_index(arr.len())?)?,
+                (
+                    JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_),
+                    Value::Object(obj),
+                ) => obj.get(&item.index_key()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(value)
+    }
+}
+
+impl<P: Property, E: Element> From<bool> for Value<'_, P, E> {
+    fn from(val: bool) -> Self {
+        Value::Bool(val)
+    }
+}
+
+impl<'a, P: Property, E: Element> From<&'a str> for Value<'a, P, E> {
+    fn from(val: &'a str) -> Self {
+        Value::Str(Cow::Borrowed(val))
+    }
+}
+
+impl<P: Property, E: Element> From<String> for Value<'_, P, E> {
+    fn from(val: String) -> Self {
+        Value::Str(Cow::Owned(val))
+    }
+}
+
+impl<'x, P: Property, E: Element> From<Cow<'x, str>> for Value<'x, P, E> {
+    fn from(val: Cow<'x, str>) -> Self {
+        Value::Str(val)
+    }
+}
+
+impl<'a, P: Property, E: Element, T: Into<Value<'a, P, E>>> From<Vec<T>> for Value<'a, P, E> {
+    fn from(val: Vec<T>) -> Self {
+        Value::Array(val.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Collects items into a `Value::Array`. To build an object, collect `(Key, Value)` pairs
+/// into an [`ObjectAsVec`] instead.
+impl<'a, P: Property, E: Element, T: Into<Value<'a, P, E>>> FromIterator<T> for Value<'a, P, E> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'a, P: Property, E: Element, V: Into<Value<'a, P, E>>> From<BTreeMap<String, V>>
+    for Value<'a, P, E>
+{
+    fn from(val: BTreeMap<String, V>) -> Self {
+        Value::Object(
+            val.into_iter()
+                .map(|(k, v)| (Key::Owned(k), v.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, P: Property, E: Element, V: Into<Value<'a, P, E>>, S: BuildHasher>
+    From<HashMap<String, V, S>> for Value<'a, P, E>
+{
+    fn from(val: HashMap<String, V, S>) -> Self {
+        Value::Object(
+            val.into_iter()
+                .map(|(k, v)| (Key::Owned(k), v.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<'x, P: Property, E: Element, T: Into<Value<'x, P, E>>> From<Option<T>> for Value<'x, P, E> {
+    fn from(val: Option<T>) -> Self {
+        match val {
+            Some(v) => v.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<'x, P: Property, E: Element> From<E> for Value<'x, P, E> {
+    fn from(element: E) -> Self {
+        Value::Element(element)
+    }
+}
+
+impl<'a, P: Property, E: Element, T: Clone + Into<Value<'a, P, E>>> From<&[T]> for Value<'a, P, E> {
+    fn from(val: &[T]) -> Self {
+        Value::Array(val.iter().map(Clone::clone).map(Into::into).collect())
+    }
+}
+
+/// Values are ordered first by variant, `Null < Bool < Number < Str < Element < Array < Object`,
+/// and then by their contents:
+///
+/// - Numbers compare by mathematical value across integers and floats. Non-finite floats are
+///   never produced by the parser, which keeps the order total.
+/// - Elements compare by their string representation. `Element` does not require `Ord`, so
+///   two elements that differ but share a representation compare as `Equal` even though
+///   `==` tells them apart, breaking the consistency of `Ord` with `Eq` for such values.
+///   Implementations whose `to_cow` is injective are not affected.
+/// - Objects compare by their entries sorted by key, see [`ObjectAsVec`] for the cost.
+impl<P: Property, E: Element> Ord for Value<'_, P, E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Element(a), Value::Element(b)) => a.to_cow().cmp(&b.to_cow()),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Object(a), Value::Object(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl<P: Property, E: Element> PartialOrd for Value<'_, P, E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Property, E: Element> Value<'_, P, E> {
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::Str(_) => 3,
+            Value::Element(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    /// Compares two values ignoring the order of object entries. Arrays are still compared
+    /// element by element, and duplicate keys have to appear the same number of times.
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.eq_unordered(b))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                let mut matched = vec![false; b.len()];
+                a.iter().all(|(key, value)| {
+                    b.iter().enumerate().any(|(pos, (k, v))| {
+                        if !matched[pos] && k == key && v.eq_unordered(value) {
+                            matched[pos] = true;
+                            true
+                        } else {
+                            false
+                        }
+                    })
+                })
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Compares two values like `==`, except that numbers involving a float are equal when
+    /// they differ by at most `epsilon` in absolute terms, or by at most `epsilon` times the
+    /// larger magnitude of the two. The absolute bound handles values close to zero while the
+    /// relative one scales with large values. Two integers are still compared exactly.
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => match (a.n, b.n) {
+                (N::Float(_), _) | (_, N::Float(_)) => {
+                    let (Some(a), Some(b)) = (a.as_f64(), b.as_f64()) else {
+                        return false;
+                    };
+                    let diff = (a - b).abs();
+                    diff <= epsilon || diff <= epsilon * a.abs().max(b.abs())
+                }
+                _ => a == b,
+            },
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.approx_eq(vb, epsilon))
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Hashes the value consistently with [`Value::eq_unordered`]: object entries are hashed
+    /// individually and combined with a commutative operation, so insertion order does not
+    /// affect the result.
+    #[cfg(feature = "std")]
+    pub fn hash_canonical<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Value::Null => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Number(n) => n.hash(state),
+            Value::Str(s) => s.hash(state),
+            Value::Element(e) => e.hash(state),
+            Value::Array(values) => {
+                values.len().hash(state);
+                for value in values {
+                    value.hash_canonical(state);
+                }
+            }
+            Value::Object(map) => {
+                let combined = map.iter().fold(0u64, |acc, (key, value)| {
+                    let mut hasher = DefaultHasher::new();
+                    key.to_string().hash(&mut hasher);
+                    value.hash_canonical(&mut hasher);
+                    acc.wrapping_add(hasher.finish())
+                });
+                map.len().hash(state);
+                combined.hash(state);
+            }
+        }
+    }
+}
+
+/// Options for [`Value::flatten_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenOptions {
+    /// Separator placed between object keys, `.` by default.
+    pub separator: String,
+    /// How array indexes are written, `a[0]` by default.
+    pub array_notation: ArrayNotation,
+    /// Whether empty arrays and objects are emitted as leaves instead of being dropped.
+    pub keep_empty: bool,
+}
+
+/// How a patch is combined with an existing value, see [`Value::merge`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum MergeStrategy<'x, P: Property> {
+    /// The existing value is replaced by the patch.
+    Replace,
+    /// Objects are merged recursively, see [`Value::deep_merge`].
+    #[default]
+    Deep,
+    /// Arrays of objects are merged by the value of the given field, see
+    /// [`Value::merge_arrays_by_key`].
+    ArraysByKey(Key<'x, P>),
+}
+
+/// Notation used for array indexes in flattened paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayNotation {
+    /// Indexes are enclosed in brackets, as in `a[0].b`.
+    #[default]
+    Brackets,
+    /// Indexes are treated like keys, as in `a.0.b`.
+    Separator,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            separator: ".".to_string(),
+            array_notation: ArrayNotation::default(),
+            keep_empty: false,
+        }
+    }
+}
+
+/// Wrapper that implements `Eq` and `Hash` through [`Value::eq_unordered`] and
+/// [`Value::hash_canonical`], so that objects with the same entries in a different order are
+/// considered equal, for example as `HashSet` members.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct CanonicalValue<'ctx, P: Property, E: Element>(pub Value<'ctx, P, E>);
+
+#[cfg(feature = "std")]
+impl<P: Property, E: Element> PartialEq for CanonicalValue<'_, P, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_unordered(&other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P: Property, E: Element> Eq for CanonicalValue<'_, P, E> {}
+
+#[cfg(feature = "std")]
+impl<P: Property, E: Element> Hash for CanonicalValue<'_, P, E> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_canonical(state);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'ctx, P: Property, E: Element> From<Value<'ctx, P, E>> for CanonicalValue<'ctx, P, E> {
+    fn from(value: Value<'ctx, P, E>) -> Self {
+        CanonicalValue(value)
+    }
+}
+
+// The following comparisons are helpers for matching against Rust primitives,
+// they have no effect on the derived `PartialEq` between two `Value`s.
+impl<P: Property, E: Element> PartialEq<str> for Value<'_, P, E> {
+    fn eq(&self, other: &str) -> bool {
+        match self {
+            Value::Str(s) => s == other,
+            Value::Element(e) => e.to_cow() == other,
+            _ => false,
+        }
+    }
+}
+
+impl<P: Property, E: Element> PartialEq<&str> for Value<'_, P, E> {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl<P: Property, E: Element> PartialEq<bool> for Value<'_, P, E> {
+    fn eq(&self, other: &bool) -> bool {
+        matches!(self, Value::Bool(b) if b == other)
+    }
+}
+
+impl<P: Property, E: Element> PartialEq<u64> for Value<'_, P, E> {
+    fn eq(&self, other: &u64) -> bool {
+        matches!(self, Value::Number(n) if n == other)
+    }
+}
+
+impl<P: Property, E: Element> PartialEq<i64> for Value<'_, P, E> {
+    fn eq(&self, other: &i64) -> bool {
+        matches!(self, Value::Number(n) if n == other)
+    }
+}
+
+impl<P: Property, E: Element> PartialEq<f64> for Value<'_, P, E> {
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Value::Number(n) if n == other)
+    }
+}
+
+impl<P: Property, E: Element> Debug for Value<'_, P, E> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => formatter.write_str("Null"),
+            Value::Bool(boolean) => write!(formatter, "Bool({})", boolean),
+            Value::Number(number) => match number.n {
+                N::PosInt(n) => write!(formatter, "Number({:?})", n),
+                N::NegInt(n) => write!(formatter, "Number({:?})", n),
+                N::Float(n) => write!(formatter, "Number({:?})", n),
+            },
+            Value::Str(string) => write!(formatter, "Str({:?})", string),
+            Value::Array(vec) => {
+                formatter.write_str("Array ")?;
+                Debug::fmt(vec, formatter)
+            }
+            Value::Object(map) => {
+                formatter.write_str("Object ")?;
+                Debug::fmt(map, formatter)
+            }
+            Value::Element(element) => write!(formatter, "Element({})", element.to_cow()),
+        }
+    }
+}
+
+/// Formats the value as minified JSON with the keys of every object sorted. This is a
+/// convenience that clones the value into a `serde_json::Value` first; use
+/// [`Value::to_json_string`] or [`Value::to_json_bytes`] when performance matters.
+impl<P: Property, E: Element> Display for Value<'_, P, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", serde_json::Value::from(self.clone()))
+    }
+}
 
 impl<P: Property, E: Element> From<u64> for Value<'_, P, E> {
     fn from(val: u64) -> Self {
@@ -436,6 +1815,46 @@ impl<'ctx, P: Property, E: Element> From<ObjectAsVec<'ctx, P, E>> for Value<'ctx
     }
 }
 
+impl<P: Property, E: Element<Property = P>> TryFrom<&Value<'_, P, E>> for bool {
+    type Error = ();
+
+    fn try_from(value: &Value<'_, P, E>) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or(())
+    }
+}
+
+impl<P: Property, E: Element<Property = P>> TryFrom<&Value<'_, P, E>> for u64 {
+    type Error = ();
+
+    fn try_from(value: &Value<'_, P, E>) -> Result<Self, Self::Error> {
+        value.as_u64().ok_or(())
+    }
+}
+
+impl<P: Property, E: Element<Property = P>> TryFrom<&Value<'_, P, E>> for i64 {
+    type Error = ();
+
+    fn try_from(value: &Value<'_, P, E>) -> Result<Self, Self::Error> {
+        value.as_i64().ok_or(())
+    }
+}
+
+impl<P: Property, E: Element<Property = P>> TryFrom<&Value<'_, P, E>> for f64 {
+    type Error = ();
+
+    fn try_from(value: &Value<'_, P, E>) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or(())
+    }
+}
+
+impl<P: Property, E: Element<Property = P>> TryFrom<&Value<'_, P, E>> for String {
+    type Error = ();
+
+    fn try_from(value: &Value<'_, P, E>) -> Result<Self, Self::Error> {
+        value.as_str().map(Cow::into_owned).ok_or(())
+    }
+}
+
 impl<P: Property, E: Element> From<Value<'_, P, E>> for serde_json::Value {
     fn from(val: Value<'_, P, E>) -> Self {
         match val {
@@ -447,7 +1866,10 @@ impl<P: Property, E: Element> From<Value<'_, P, E>> for serde_json::Value {
                 serde_json::Value::Array(vals.into_iter().map(|val| val.into()).collect())
             }
             Value::Object(vals) => serde_json::Value::Object(vals.into()),
-            Value::Element(element) => serde_json::Value::String(element.to_cow().to_string()),
+            Value::Element(element) => match element.serialize_as() {
+                ElementRepr::String(s) => serde_json::Value::String(s.into_owned()),
+                ElementRepr::Value(value) => value.into(),
+            },
         }
     }
 }
@@ -457,13 +1879,16 @@ impl<P: Property, E: Element> From<&Value<'_, P, E>> for serde_json::Value {
         match val {
             Value::Null => serde_json::Value::Null,
             Value::Bool(val) => serde_json::Value::Bool(*val),
-            Value::Number(val) => serde_json::Value::Number((*val).into()),
+            Value::Number(val) => serde_json::Value::Number(val.into()),
             Value::Str(val) => serde_json::Value::String(val.to_string()),
             Value::Array(vals) => {
                 serde_json::Value::Array(vals.iter().map(|val| val.into()).collect())
             }
             Value::Object(vals) => serde_json::Value::Object(vals.into()),
-            Value::Element(element) => serde_json::Value::String(element.to_cow().to_string()),
+            Value::Element(element) => match element.serialize_as() {
+                ElementRepr::String(s) => serde_json::Value::String(s.into_owned()),
+                ElementRepr::Value(value) => value.into(),
+            },
         }
     }
 }
@@ -473,31 +1898,60 @@ impl<'ctx, P: Property, E: Element> From<&'ctx serde_json::Value> for Value<'ctx
         match value {
             serde_json::Value::Null => Value::Null,
             serde_json::Value::Bool(b) => Value::Bool(*b),
-            serde_json::Value::Number(n) => {
-                if let Some(n) = n.as_i64() {
-                    Value::Number(n.into())
-                } else if let Some(n) = n.as_u64() {
-                    Value::Number(n.into())
-                } else if let Some(n) = n.as_f64() {
-                    Value::Number(n.into())
-                } else {
-                    unreachable!()
-                }
-            }
+            serde_json::Value::Number(n) => Value::Number(number_from_json(n)),
             serde_json::Value::String(val) => Value::Str(Cow::Borrowed(val)),
             serde_json::Value::Array(arr) => {
                 let out: Vec<Value<'ctx, P, E>> = arr.iter().map(|v| v.into()).collect();
                 Value::Array(out)
             }
-            serde_json::Value::Object(obj) => {
-                let mut ans = ObjectAsVec(Vec::with_capacity(obj.len()));
-                for (k, v) in obj {
-                    ans.insert(Key::Borrowed(k.as_str()), v);
-                }
-                Value::Object(ans)
+            serde_json::Value::Object(obj) => Value::Object(obj.into()),
+        }
+    }
+}
+
+impl<P: Property, E: Element> From<serde_json::Value> for Value<'static, P, E> {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(b),
+            serde_json::Value::Number(n) => Value::Number(number_from_json(&n)),
+            serde_json::Value::String(val) => Value::Str(Cow::Owned(val)),
+            serde_json::Value::Array(arr) => {
+                Value::Array(arr.into_iter().map(Into::into).collect())
             }
+            serde_json::Value::Object(obj) => Value::Object(obj.into()),
+        }
+    }
+}
+
+/// Returns the HTML-escaped form of `text`, or None if it contains nothing to escape.
+fn escape_html(text: &str) -> Option<String> {
+    let first = text.find(['&', '<', '>', '"', '\''])?;
+    let mut escaped = String::with_capacity(text.len() + 8);
+    escaped.push_str(&text[..first]);
+    for ch in text[first..].chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
         }
     }
+    Some(escaped)
+}
+
+fn number_from_json(n: &serde_json::Number) -> Number {
+    if let Some(n) = n.as_i64() {
+        n.into()
+    } else if let Some(n) = n.as_u64() {
+        n.into()
+    } else if let Some(n) = n.as_f64() {
+        n.into()
+    } else {
+        unreachable!()
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
@@ -573,6 +2027,454 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn flatten() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{"a": {"b": [{"c": 1}, [true, null]], "e": {}}, "f": [], "g": "x"}"#,
+        )
+        .unwrap();
+        let paths = |options: &FlattenOptions| {
+            value
+                .flatten_with(options)
+                .into_iter()
+                .map(|(path, value)| format!("{path}={value}"))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(&FlattenOptions::default()),
+            ["a.b[0].c=1", "a.b[1][0]=true", "a.b[1][1]=null", "g=\"x\""]
+        );
+        assert_eq!(
+            paths(&FlattenOptions {
+                separator: "/".to_string(),
+                array_notation: ArrayNotation::Separator,
+                keep_empty: true,
+            }),
+            [
+                "a/b/0/c=1",
+                "a/b/1/0=true",
+                "a/b/1/1=null",
+                "a/e={}",
+                "f=[]",
+                "g=\"x\""
+            ]
+        );
+
+        let array = Value::<'_, Null, Null>::parse_json(r#"[[1], 2]"#).unwrap();
+        assert_eq!(
+            array
+                .flatten()
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            ["[0][0]", "[1]"]
+        );
+        assert_eq!(
+            Value::<'_, Null, Null>::Bool(true).flatten(),
+            [(String::new(), Value::Bool(true))]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn canonical_value() {
+        use std::collections::HashSet;
+
+        let a = Value::<'_, Null, Null>::parse_json(
+            r#"{"a": 1, "b": {"c": [1, {"x": 1, "y": 2}], "d": null}}"#,
+        )
+        .unwrap();
+        let b = Value::<'_, Null, Null>::parse_json(
+            r#"{"b": {"d": null, "c": [1, {"y": 2, "x": 1}]}, "a": 1}"#,
+        )
+        .unwrap();
+        let c = Value::<'_, Null, Null>::parse_json(
+            r#"{"b": {"d": null, "c": [{"y": 2, "x": 1}, 1]}, "a": 1}"#,
+        )
+        .unwrap();
+
+        assert_ne!(a, b);
+        assert!(a.eq_unordered(&b));
+        assert!(!a.eq_unordered(&c));
+
+        let set = HashSet::from([CanonicalValue(a), CanonicalValue(b), CanonicalValue(c)]);
+        assert_eq!(set.len(), 2);
+
+        let obj = |entries: [(&'static str, u64); 3]| {
+            Value::<'_, Null, Null>::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Key::Borrowed(k), Value::Number(v.into())))
+                    .collect(),
+            )
+        };
+        assert!(obj([("a", 1), ("a", 2), ("b", 3)]).eq_unordered(&obj([
+            ("b", 3),
+            ("a", 2),
+            ("a", 1)
+        ])));
+        assert!(!obj([("a", 1), ("a", 1), ("b", 3)]).eq_unordered(&obj([
+            ("a", 1),
+            ("b", 3),
+            ("b", 3)
+        ])));
+    }
+
+    #[test]
+    fn coerce() {
+        let num = |n: f64| Some(Number::from(n));
+        for (json, string, number) in [
+            ("null", "", None),
+            ("true", "true", Some(Number::from(N::PosInt(1)))),
+            ("false", "false", Some(Number::from(N::PosInt(0)))),
+            ("42", "42", Some(Number::from(N::PosInt(42)))),
+            ("-7", "-7", Some(Number::from(N::NegInt(-7)))),
+            ("1.5", "1.5", num(1.5)),
+            (r#""12""#, "12", Some(Number::from(N::PosInt(12)))),
+            (r#"" -3 ""#, " -3 ", Some(Number::from(N::NegInt(-3)))),
+            (r#""-0""#, "-0", Some(Number::from(N::PosInt(0)))),
+            (r#""2.5e3""#, "2.5e3", num(2500.0)),
+            ("\"\"", "", None),
+            (r#""abc""#, "abc", None),
+            (r#""inf""#, "inf", None),
+            ("[1]", "", None),
+            (r#"{"a": 1}"#, "", None),
+        ] {
+            let value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+            assert_eq!(value.coerce_to_string(), string, "{json}");
+            assert!(value.coerce_to_number() == number, "{json}");
+        }
+    }
+
+    #[test]
+    fn typed_array_extractors() {
+        let ids = Value::<'_, Null, Null>::parse_json("[1, 2, 3]").unwrap();
+        assert_eq!(ids.as_u64_vec(), Some(vec![1, 2, 3]));
+        assert_eq!(ids.as_str_vec(), None);
+        assert_eq!(ids.collect_bool_vec(), Vec::<bool>::new());
+
+        let keywords = Value::<'_, Null, Null>::parse_json(r#"["$seen", "$flagged"]"#).unwrap();
+        assert_eq!(
+            keywords.as_str_vec(),
+            Some(vec![Cow::Borrowed("$seen"), Cow::Borrowed("$flagged")])
+        );
+
+        let mixed =
+            Value::<'_, Null, Null>::parse_json(r#"[true, 1, "a", false, -2, null]"#).unwrap();
+        assert_eq!(mixed.as_u64_vec(), None);
+        assert_eq!(mixed.as_str_vec(), None);
+        assert_eq!(mixed.as_bool_vec(), None);
+        assert_eq!(mixed.collect_u64_vec(), vec![1]);
+        assert_eq!(mixed.collect_str_vec(), vec![Cow::Borrowed("a")]);
+        assert_eq!(mixed.collect_bool_vec(), vec![true, false]);
+
+        let empty = Value::<'_, Null, Null>::parse_json("[]").unwrap();
+        assert_eq!(empty.as_u64_vec(), Some(vec![]));
+
+        let not_array = Value::<'_, Null, Null>::Number(1u64.into());
+        assert_eq!(not_array.as_u64_vec(), None);
+        assert_eq!(not_array.collect_u64_vec(), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn pick_omit() {
+        let value =
+            Value::<'_, Null, Null>::parse_json(r#"{"id": "a", "subject": "b", "size": 1}"#)
+                .unwrap();
+
+        assert_eq!(
+            value.pick(Some(&[
+                "size".into(),
+                "missing".into(),
+                "id".into(),
+                "size".into()
+            ])),
+            Value::parse_json(r#"{"size": 1, "id": "a"}"#).unwrap()
+        );
+        assert_eq!(value.pick(Some(&[])), Value::Object(ObjectAsVec::new()));
+        assert_eq!(value.pick(None), value);
+        assert_eq!(
+            value.omit(&["subject".into(), "missing".into()]),
+            Value::parse_json(r#"{"id": "a", "size": 1}"#).unwrap()
+        );
+
+        let array = Value::<'_, Null, Null>::parse_json("[1, 2]").unwrap();
+        assert_eq!(array.pick(Some(&["id".into()])), array);
+        assert_eq!(array.omit(&["id".into()]), array);
+    }
+
+    #[test]
+    fn approx_eq() {
+        let num = |n: f64| Value::<'_, Null, Null>::Number(n.into());
+
+        assert_ne!(num(0.1 + 0.2), num(0.3));
+        assert!(num(0.1 + 0.2).approx_eq(&num(0.3), 1e-9));
+
+        // Absolute tolerance near zero
+        assert!(num(0.0).approx_eq(&num(0.25), 0.25));
+        assert!(!num(0.0).approx_eq(&num(0.2500001), 0.25));
+
+        // Relative tolerance for large values
+        assert!(num(100.0).approx_eq(&num(125.0), 0.25));
+        assert!(!num(100.0).approx_eq(&num(134.0), 0.25));
+
+        // Integers are converted when compared against floats, but not against each other
+        let int = |n: u64| Value::<'_, Null, Null>::Number(n.into());
+        assert!(int(100).approx_eq(&num(125.0), 0.25));
+        assert!(num(-1.0).approx_eq(&Value::Number((-1i64).into()), 0.0));
+        assert!(!int(100).approx_eq(&int(101), 0.25));
+
+        let a =
+            Value::<'_, Null, Null>::parse_json(r#"{"a": [1.0, "x"], "b": 0.30000000000000004}"#)
+                .unwrap();
+        let b =
+            Value::<'_, Null, Null>::parse_json(r#"{"a": [1.0000000001, "x"], "b": 0.3}"#).unwrap();
+        assert!(a.approx_eq(&b, 1e-9));
+        assert!(!a.approx_eq(&b, 1e-12));
+        let c = Value::<'_, Null, Null>::parse_json(r#"{"a": [1.0, "y"], "b": 0.3}"#).unwrap();
+        assert!(!a.approx_eq(&c, 1e-9));
+    }
+
+    #[test]
+    fn single_entry() {
+        let empty = Value::<'_, Null, Null>::parse_json("{}").unwrap();
+        assert_eq!(empty.as_single_entry(), None);
+        assert_eq!(empty.into_single_entry(), None);
+
+        let one = Value::<'_, Null, Null>::parse_json(r#"{"a": true}"#).unwrap();
+        assert_eq!(
+            one.as_single_entry(),
+            Some((&Key::Borrowed("a"), &Value::Bool(true)))
+        );
+        assert_eq!(
+            one.into_single_entry(),
+            Some((Key::Borrowed("a"), Value::Bool(true)))
+        );
+
+        let many = Value::<'_, Null, Null>::parse_json(r#"{"a": true, "b": false}"#).unwrap();
+        assert_eq!(many.as_single_entry(), None);
+        assert_eq!(many.into_single_entry(), None);
+
+        let array = Value::<'_, Null, Null>::parse_json("[{}]").unwrap();
+        assert_eq!(array.as_single_entry(), None);
+    }
+
+    #[test]
+    fn is_empty_len() {
+        for (json, is_empty, len) in [
+            ("null", true, None),
+            ("\"\"", true, Some(0)),
+            ("[]", true, Some(0)),
+            ("{}", true, Some(0)),
+            ("\"héllo\"", false, Some(5)),
+            ("[null, 1]", false, Some(2)),
+            (r#"{"a": null}"#, false, Some(1)),
+            ("0", false, None),
+            ("false", false, None),
+        ] {
+            let value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+            assert_eq!(value.is_empty(), is_empty, "{json}");
+            assert_eq!(value.len(), len, "{json}");
+        }
+    }
+
+    #[test]
+    fn parse_json_bytes() {
+        let json = br#"{"subject": "hello", "to": ["a\u0040b"]}"#.to_vec();
+        let value = Value::<'_, Null, Null>::parse_json_bytes(&json).unwrap();
+
+        let Value::Str(Cow::Borrowed(subject)) = value.get("subject") else {
+            panic!("expected a borrowed string: {value:?}");
+        };
+        assert!(json.as_ptr_range().contains(&subject.as_ptr()));
+        assert_eq!(*subject, "hello");
+        assert_eq!(
+            value.get("to").as_array().unwrap()[0],
+            Value::Str(Cow::Owned("a@b".to_string()))
+        );
+
+        assert!(Value::<'_, Null, Null>::parse_json_bytes(b"\"\xff\"").is_err());
+        assert!(Value::<'_, Null, Null>::parse_json_bytes(b"{").is_err());
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    enum Tag {
+        Seen,
+        Custom(u64),
+    }
+
+    impl Element for Tag {
+        type Property = Null;
+
+        fn try_parse<P>(_: &Key<'_, Null>, _: &str) -> Option<Self> {
+            None
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            match self {
+                Tag::Seen => "$seen".into(),
+                Tag::Custom(n) => format!("$tag{n}").into(),
+            }
+        }
+    }
+
+    #[test]
+    fn as_borrowed_str() {
+        let json = String::from("text");
+        let borrowed = Value::<'_, Null, Tag>::Str(Cow::Borrowed(&json));
+        let owned = Value::<'_, Null, Tag>::Str(Cow::Owned("text".to_string()));
+        assert_eq!(borrowed.as_borrowed_str(), Some("text"));
+        assert!(std::ptr::eq(
+            borrowed.as_borrowed_str().unwrap(),
+            json.as_str()
+        ));
+        assert_eq!(owned.as_borrowed_str(), Some("text"));
+
+        // Elements only borrow when their string form does not have to be built
+        assert_eq!(
+            Value::<'_, Null, Tag>::Element(Tag::Seen).as_borrowed_str(),
+            Some("$seen")
+        );
+        assert_eq!(
+            Value::<'_, Null, Tag>::Element(Tag::Custom(1)).as_borrowed_str(),
+            None
+        );
+        assert_eq!(
+            Value::<'_, Null, Tag>::Element(Tag::Custom(1)).as_str(),
+            Some(Cow::Owned("$tag1".to_string()))
+        );
+
+        for value in [
+            Value::<'_, Null, Tag>::Null,
+            Value::Bool(true),
+            Value::Number(1u64.into()),
+            Value::Array(vec![Value::Str("text".into())]),
+        ] {
+            assert_eq!(value.as_borrowed_str(), None);
+        }
+    }
+
+    #[test]
+    fn sort_keys_by() {
+        let priority = |key: &Key<'_, Null>| {
+            ["id", "name"]
+                .iter()
+                .position(|p| key == p)
+                .unwrap_or(usize::MAX)
+        };
+        let by_priority = |a: &Key<'_, Null>, b: &Key<'_, Null>| {
+            priority(a)
+                .cmp(&priority(b))
+                .then_with(|| a.to_string().cmp(&b.to_string()))
+        };
+
+        let json = r#"{"size": 1, "name": "a", "blob": {"z": 1, "id": 2, "a": 3}, "id": "x",
+                       "parts": [{"type": "text", "name": "b", "id": "y"}]}"#;
+        let mut value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+        value.sort_keys_by(by_priority);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            concat!(
+                r#"{"id":"x","name":"a","blob":{"z":1,"id":2,"a":3},"parts":"#,
+                r#"[{"type":"text","name":"b","id":"y"}],"size":1}"#
+            )
+        );
+
+        let mut value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+        value.sort_keys_by_recursive(by_priority);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            concat!(
+                r#"{"id":"x","name":"a","blob":{"id":2,"a":3,"z":1},"parts":"#,
+                r#"[{"id":"y","name":"b","type":"text"}],"size":1}"#
+            )
+        );
+    }
+
+    #[test]
+    fn push_to_array() {
+        let mut value =
+            Value::<'_, Null, Null>::parse_json(r#"{"list": [1], "empty": null, "text": "a"}"#)
+                .unwrap();
+
+        // Missing key
+        assert!(value.push_to_array(&Key::Borrowed("new"), Value::Number(1u64.into())));
+        // Existing array
+        assert!(value.push_to_array(&Key::Borrowed("list"), Value::Number(2u64.into())));
+        // Null value
+        assert_eq!(
+            value.ensure_array(&Key::Borrowed("empty")),
+            Some(&mut vec![])
+        );
+        // Type mismatch
+        assert!(!value.push_to_array(&Key::Borrowed("text"), Value::Null));
+        assert!(
+            Value::<'_, Null, Null>::Null
+                .ensure_array(&Key::Borrowed("a"))
+                .is_none()
+        );
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"empty":[],"list":[1,2],"new":[1],"text":"a"}"#
+        );
+    }
+
+    #[test]
+    fn truncate_strings() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{"short": "abc", "long": "abcdefgh", "list": ["ñandú ñandú", "ok"], "n": 12345678}"#,
+        )
+        .unwrap();
+
+        let truncated = value.to_truncated(5);
+        assert_eq!(
+            truncated.to_string(),
+            r#"{"list":["ñandú…","ok"],"long":"abcde…","n":12345678,"short":"abc"}"#
+        );
+        assert_ne!(truncated, value);
+
+        let mut value = value;
+        value.truncate_strings(0);
+        assert_eq!(value.get("short"), &Value::Str("…".into()));
+    }
+
+    #[test]
+    fn as_container_or_insert() {
+        let mut root = Value::<'_, Null, Null>::Null;
+        root.as_object_or_insert().insert("a", 1u64);
+        root.as_object_or_insert().insert("b", Value::Null);
+        root.as_object_or_insert()
+            .get_mut(&Key::Borrowed("b"))
+            .unwrap()
+            .as_array_or_insert()
+            .push(Value::Bool(true));
+        assert_eq!(root.to_string(), r#"{"a":1,"b":[true]}"#);
+
+        let mut list = Value::<'_, Null, Null>::Array(vec![Value::Null]);
+        list.as_array_or_insert().push(Value::Null);
+        assert_eq!(list.to_string(), "[null,null]");
+    }
+
+    #[test]
+    #[should_panic(expected = "as_object_or_insert called on a non-object value")]
+    fn as_object_or_insert_mismatch() {
+        Value::<'_, Null, Null>::Bool(true).as_object_or_insert();
+    }
+
+    #[test]
+    fn base64_str() {
+        let value = Value::<'_, Null, Null>::base64_encode_str(b"hello\x00\xff");
+        assert_eq!(value, "aGVsbG8A/w==");
+        assert_eq!(value.base64_decode().unwrap(), b"hello\x00\xff");
+        assert_eq!(
+            Value::<'_, Null, Null>::Str("not base64!".into()).base64_decode(),
+            None
+        );
+        assert_eq!(Value::<'_, Null, Null>::Null.base64_decode(), None);
+    }
+
     #[test]
     fn from_serde() {
         let value = &serde_json::json!({
@@ -590,6 +2492,524 @@ mod tests {
         assert_eq!(value.get("d").get("e"), &Value::Str("alo".into()));
     }
 
+    #[test]
+    fn get_str_pointer() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": {"b": [10, {"c": "d"}]}, "1": true}"#).unwrap();
+
+        assert_eq!(
+            value.get_str_pointer("/a/b/0"),
+            Some(&Value::Number(10u64.into()))
+        );
+        assert_eq!(
+            value.get_str_pointer("a/b/1/c"),
+            Some(&Value::Str("d".into()))
+        );
+        assert_eq!(value.get_str_pointer("/1"), Some(&Value::Bool(true)));
+        assert_eq!(value.get_str_pointer(""), Some(&value));
+        assert_eq!(value.get_str_pointer("/a/b/2"), None);
+        assert_eq!(value.get_str_pointer("/a/*"), None);
+    }
+
+    #[test]
+    fn strip_nulls() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": null, "b": {"c": null, "d": 1}, "e": [null, {"f": null}]}"#)
+                .unwrap();
+
+        assert_eq!(
+            value.without_nulls(),
+            Value::parse_json(r#"{"b": {"d": 1}, "e": [null, {}]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn depth_and_count_nodes() {
+        let value: Value<'_, Null, Null> = Value::parse_json(r#"{"a": 1, "b": "c"}"#).unwrap();
+        assert_eq!(value.depth(), 1);
+        assert_eq!(value.count_nodes(), 3);
+
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"[1, [2, [3, []]], {"a": [4]}]"#).unwrap();
+        assert_eq!(value.depth(), 4);
+        assert_eq!(value.count_nodes(), 10);
+
+        let value: Value<'_, Null, Null> = Value::parse_json("{}").unwrap();
+        assert_eq!(value.depth(), 1);
+        assert_eq!(value.count_nodes(), 1);
+
+        let value: Value<'_, Null, Null> = Value::Null;
+        assert_eq!(value.depth(), 0);
+        assert_eq!(value.count_nodes(), 1);
+    }
+
+    #[test]
+    fn make_owned() {
+        let json = r#"{"a": "b", "c": ["d", {"e": 1}]}"#;
+        let mut value: Value<'_, Null, Null> = Value::parse_json(json).unwrap();
+        let expected = value.clone();
+        value.make_owned();
+
+        assert_eq!(value, expected);
+        assert!(matches!(
+            value.as_object_and_get(&Key::Borrowed("a")),
+            Some(Value::Str(Cow::Owned(_)))
+        ));
+        assert!(matches!(
+            value.get_str_pointer("c/1").and_then(|v| v.as_object()),
+            Some(obj) if obj.keys().all(|k| matches!(k, Key::Owned(_)))
+        ));
+        assert_eq!(value.into_owned(), expected.into_owned());
+    }
+
+    #[test]
+    fn to_owned_value() {
+        let json = String::from(r#"{"a": "b", "c": ["d", {"e": 1.5}], "f": null}"#);
+        let value: Value<'_, Null, Null> = Value::parse_json(&json).unwrap();
+        let owned = value.to_owned_value();
+        drop(json);
+
+        assert_eq!(
+            owned,
+            Value::parse_json(r#"{"a": "b", "c": ["d", {"e": 1.5}], "f": null}"#).unwrap()
+        );
+        assert!(matches!(
+            owned.as_object_and_get(&Key::Borrowed("a")),
+            Some(Value::Str(Cow::Owned(_)))
+        ));
+        assert!(
+            owned
+                .as_object()
+                .unwrap()
+                .keys()
+                .all(|k| matches!(k, Key::Owned(_)))
+        );
+    }
+
+    #[test]
+    fn from_btree_map() {
+        let mut map = BTreeMap::new();
+        map.insert("c".to_string(), 3u64);
+        map.insert("a".to_string(), 1u64);
+        map.insert("b".to_string(), 2u64);
+
+        let value: Value<'_, Null, Null> = map.into();
+        assert_eq!(
+            value
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|k| k.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(value.get("b"), &Value::Number(2u64.into()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn from_hash_map() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("a".to_string(), "x");
+        map.insert("b".to_string(), "y");
+
+        let value: Value<'_, Null, Null> = map.into();
+        assert_eq!(value.as_object().unwrap().len(), 2);
+        assert_eq!(value.get("a"), &Value::Str("x".into()));
+        assert_eq!(value.get("b"), &Value::Str("y".into()));
+    }
+
+    #[test]
+    fn map_values_and_visit() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": "b", "c": ["d", 1, {"e": "f"}]}"#).unwrap();
+
+        let mut order = Vec::new();
+        value.visit(|v| order.push(v.to_string()));
+        assert_eq!(
+            order,
+            vec![
+                r#""b""#,
+                r#""d""#,
+                "1",
+                r#""f""#,
+                r#"{"e":"f"}"#,
+                r#"["d",1,{"e":"f"}]"#,
+                r#"{"a":"b","c":["d",1,{"e":"f"}]}"#
+            ]
+        );
+
+        let value = value.map_values(|v| match v {
+            Value::Str(s) => Value::Str(s.to_uppercase().into()),
+            v => v,
+        });
+        assert_eq!(
+            value,
+            Value::parse_json(r#"{"a": "B", "c": ["D", 1, {"e": "F"}]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn walk_mut() {
+        let mut value: Value<'_, Null, Null> = Value::parse_json(
+            r#"{"users": [
+                {"name": "a", "credentials": {"password": "x", "tokens": ["y", 1]}},
+                {"name": "b"}
+            ], "credentials": null}"#,
+        )
+        .unwrap();
+
+        let mut paths = Vec::new();
+        let pattern = JsonPointer::parse("users/*/credentials/**");
+        value.walk_mut(|path, v| {
+            paths.push(path.to_string());
+            if pattern.matches_path(path) && matches!(v, Value::Str(_)) {
+                *v = Value::Null;
+            }
+        });
+
+        assert_eq!(
+            value,
+            Value::parse_json(
+                r#"{"users": [
+                    {"name": "a", "credentials": {"password": null, "tokens": [null, 1]}},
+                    {"name": "b"}
+                ], "credentials": null}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            paths,
+            vec![
+                "",
+                "users",
+                "users/0",
+                "users/0/name",
+                "users/0/credentials",
+                "users/0/credentials/password",
+                "users/0/credentials/tokens",
+                "users/0/credentials/tokens/0",
+                "users/0/credentials/tokens/1",
+                "users/1",
+                "users/1/name",
+                "credentials",
+            ]
+        );
+
+        // Children are visited as left by the callback
+        let mut value: Value<'_, Null, Null> = Value::parse_json(r#"{"a": 1}"#).unwrap();
+        value.walk_mut(|path, v| {
+            if path.as_slice() == [JsonPointerItem::Key("a".into())] {
+                *v = Value::Array(vec![Value::Bool(true)]);
+            } else if let Value::Bool(b) = v {
+                *b = false;
+            }
+        });
+        assert_eq!(value, Value::parse_json(r#"{"a": [false]}"#).unwrap());
+    }
+
+    #[test]
+    fn partial_eq_primitives() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"s": "text", "b": true, "u": 5, "i": -5, "f": 5.0}"#).unwrap();
+
+        assert_eq!(*value.get("s"), "text");
+        assert!(*value.get("s") == *"text");
+        assert_ne!(*value.get("s"), "other");
+        assert_eq!(*value.get("b"), true);
+        assert_eq!(*value.get("u"), 5u64);
+        assert_eq!(*value.get("u"), 5i64);
+        assert_eq!(*value.get("u"), 5.0f64);
+        assert_eq!(*value.get("i"), -5i64);
+        assert_ne!(*value.get("i"), 5u64);
+        assert_eq!(*value.get("f"), 5u64);
+        assert_eq!(*value.get("f"), 5i64);
+        assert_eq!(Value::<'_, Null, Null>::Number(5i64.into()), 5u64);
+        assert_ne!(*value.get("u"), "5");
+        assert_ne!(*value.get("s"), false);
+    }
+
+    #[test]
+    fn get_opt() {
+        fn lookup<'a>(value: &'a Value<'_, Null, Null>) -> Option<&'a Value<'a, Null, Null>> {
+            value.get_opt("a")?.get_opt("b")?.get_opt(1)
+        }
+
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": {"b": [null, "c"]}, "d": null}"#).unwrap();
+
+        assert_eq!(lookup(&value), Some(&Value::Str("c".into())));
+        assert_eq!(value.get_opt("d"), Some(&Value::Null));
+        assert_eq!(value.get_opt("e"), None);
+        assert_eq!(value.get("e"), &Value::Null);
+    }
+
+    #[test]
+    fn get_as() {
+        let value: Value<'_, Null, Null> = Value::parse_json(
+            r#"{"count": 3, "delta": -2, "ratio": 0.5, "name": "Inbox", "flag": true, "none": null}"#,
+        )
+        .unwrap();
+
+        assert_eq!(value.get_as::<u64>("count"), Some(3));
+        assert_eq!(value.get_as::<i64>("delta"), Some(-2));
+        assert_eq!(value.get_as::<f64>("ratio"), Some(0.5));
+        assert_eq!(value.get_as::<String>("name"), Some("Inbox".to_string()));
+        assert_eq!(value.get_as::<bool>("flag"), Some(true));
+
+        assert_eq!(value.get_as::<u64>("delta"), None);
+        assert_eq!(value.get_as::<u64>("name"), None);
+        assert_eq!(value.get_as::<String>("count"), None);
+        assert_eq!(value.get_as::<bool>("none"), None);
+
+        assert_eq!(value.get_as::<u64>("missing"), None);
+        assert_eq!(value.get_as::<u64>(0), None);
+    }
+
+    #[test]
+    fn entries_mut() {
+        let mut value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"b": 1, "c": 2, "a": 3}"#).unwrap();
+
+        let entries = value.entries_mut().unwrap();
+        for (_, value) in entries.iter_mut() {
+            *value = Value::Number((value.as_u64().unwrap() * 10).into());
+        }
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        assert_eq!(value.get("a").as_u64(), Some(30));
+        assert_eq!(value.get("b").as_u64(), Some(10));
+        assert_eq!(value.get("c").as_u64(), Some(20));
+        assert_eq!(value.to_json_string(), r#"{"a":30,"b":10,"c":20}"#);
+
+        assert!(Value::<Null, Null>::Array(vec![]).entries_mut().is_none());
+        assert!(Value::<Null, Null>::Null.entries_mut().is_none());
+    }
+
+    #[test]
+    fn collect_array() {
+        let value: Value<'_, Null, Null> = (1u64..=3).collect();
+        assert_eq!(value.to_json_string(), "[1,2,3]");
+
+        let value: Value<'_, Null, Null> = ["a", "b"].into_iter().collect();
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Str("a".into()), Value::Str("b".into())])
+        );
+
+        let value: Value<'_, Null, Null> = core::iter::empty::<bool>().collect();
+        assert_eq!(value, Value::Array(vec![]));
+    }
+
+    #[test]
+    fn escape_strings_for_html() {
+        let value: Value<'_, Null, Null> = Value::parse_json(
+            r#"{
+                "subject": "<b>Tom & Jerry's \"show\"</b>",
+                "<key>": ["plain", 1, true, null, {"nested": "a<b"}]
+            }"#,
+        )
+        .unwrap();
+
+        let escaped = value.to_html_escaped();
+        assert_eq!(
+            escaped.to_json_string(),
+            concat!(
+                r#"{"subject":"&lt;b&gt;Tom &amp; Jerry&#39;s &quot;show&quot;&lt;/b&gt;","#,
+                r#""<key>":["plain",1,true,null,{"nested":"a&lt;b"}]}"#
+            )
+        );
+        assert!(matches!(
+            escaped.get("<key>").get(0),
+            Value::Str(Cow::Borrowed("plain"))
+        ));
+
+        let mut value = value;
+        value.escape_strings_for_html();
+        assert_eq!(value, escaped);
+    }
+
+    #[test]
+    fn or_defaults() {
+        let value: Value<'_, Null, Null> =
+            Value::parse_json(r#"{"a": {"b": 1}, "c": null, "d": false}"#).unwrap();
+        let default = Value::Str("default".into());
+
+        assert_eq!(value.get("a").get_or("b", &default).as_u64(), Some(1));
+        assert_eq!(value.get_or("c", &default), &default);
+        assert_eq!(value.get_or("d", &default), &Value::Bool(false));
+        assert_eq!(value.get_or("e", &default), &default);
+        assert_eq!(value.get_or(0, &default), &default);
+
+        assert_eq!(Value::<Null, Null>::Null.or(default.clone()), default);
+        assert_eq!(
+            Value::<Null, Null>::Bool(false).or(default.clone()),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            Value::<Null, Null>::Null.or_else(|| default.clone()),
+            default
+        );
+        assert_eq!(
+            Value::<Null, Null>::Bool(true).or_else(|| unreachable!()),
+            Value::Bool(true)
+        );
+    }
+
+    #[test]
+    fn total_order() {
+        let mut values: Vec<Value<'_, Null, Null>> = Value::parse_json(
+            r#"[{"b": 1, "a": 2}, [2], "b", 2.5, null, -3, true, [1, 2], "a", false,
+                {"a": 1}, 2, {"a": 2, "b": 1}, 1e30, -3.5]"#,
+        )
+        .unwrap()
+        .into_array()
+        .unwrap();
+        values.sort();
+
+        assert_eq!(
+            serde_json::to_string(&values).unwrap(),
+            concat!(
+                r#"[null,false,true,-3.5,-3,2,2.5,1e+30,"a","b",[1,2],[2],"#,
+                r#"{"a":1},{"a":2,"b":1},{"b":1,"a":2}]"#
+            )
+        );
+
+        let a: Value<'_, Null, Null> = Value::Number(5u64.into());
+        let b: Value<'_, Null, Null> = Value::Number(5.0.into());
+        let c: Value<'_, Null, Null> = Value::Number(5i64.into());
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+        assert_ne!(a.cmp(&c), Ordering::Equal);
+        assert!(Value::<'_, Null, Null>::Number(u64::MAX.into()) > Value::Number((-1i64).into()));
+        assert!(Value::<'_, Null, Null>::Number(4.9.into()) < a);
+        assert!(Value::<'_, Null, Null>::Number(5.1.into()) > a);
+
+        let set = [a.clone(), a, Value::Null]
+            .into_iter()
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn merge_arrays_by_key() {
+        let mut value: Value<'_, Null, Null> = Value::parse_json(
+            r#"[{"id": "a", "name": "Inbox", "role": {"name": "inbox"}}, {"id": "b", "name": "Sent"}]"#,
+        )
+        .unwrap();
+        value.merge_arrays_by_key(
+            Value::parse_json(
+                r#"[{"id": "a", "role": {"sort": 1}}, {"id": "c", "name": "Trash"}, "x", {"name": "Junk"}]"#,
+            )
+            .unwrap(),
+            &Key::Borrowed("id"),
+        );
+        assert_eq!(
+            value,
+            Value::parse_json(
+                r#"[{"id": "a", "name": "Inbox", "role": {"name": "inbox", "sort": 1}},
+                    {"id": "b", "name": "Sent"}, {"id": "c", "name": "Trash"}, "x", {"name": "Junk"}]"#
+            )
+            .unwrap()
+        );
+
+        let mut value: Value<'_, Null, Null> = Value::parse_json(r#"[{"id": 1, "v": 1}]"#).unwrap();
+        value.merge_arrays_by_key(
+            Value::parse_json(r#"[{"id": 2, "v": 2}]"#).unwrap(),
+            &Key::Borrowed("id"),
+        );
+        assert_eq!(
+            value,
+            Value::parse_json(r#"[{"id": 1, "v": 1}, {"id": 2, "v": 2}]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn retain_array() {
+        let mut value: Value<'_, Null, Null> = Value::parse_json(
+            r#"[{"id": "a", "items": [{"id": null}, {"id": "c"}]}, {"id": null}, {"id": "b"}, 1]"#,
+        )
+        .unwrap();
+        let has_id = |v: &Value<'_, Null, Null>| {
+            v.as_object_and_get(&Key::Borrowed("id"))
+                .is_some_and(|id| !id.is_null())
+        };
+
+        let mut filtered = value.clone();
+        assert!(filtered.retain_array(has_id));
+        assert_eq!(
+            filtered,
+            Value::parse_json(
+                r#"[{"id": "a", "items": [{"id": null}, {"id": "c"}]}, {"id": "b"}]"#
+            )
+            .unwrap()
+        );
+
+        value.retain_array_recursive(has_id);
+        assert_eq!(
+            value,
+            Value::parse_json(r#"[{"id": "a", "items": [{"id": "c"}]}, {"id": "b"}]"#).unwrap()
+        );
+
+        let mut value: Value<'_, Null, Null> = Value::parse_json(r#"{"a": [1]}"#).unwrap();
+        assert!(!value.retain_array(|_| false));
+        value.retain_array_recursive(|_| false);
+        assert_eq!(value, Value::parse_json(r#"{"a": []}"#).unwrap());
+    }
+
+    #[test]
+    fn merge_at() {
+        let mut value: Value<'_, Null, Null> = Value::parse_json(
+            r#"{"mailboxes": [{"id": "a", "role": {"name": "inbox", "sort": 1}}], "n": 1}"#,
+        )
+        .unwrap();
+
+        // Merge into an existing nested object
+        assert!(value.merge_at(
+            JsonPointer::parse("mailboxes/0/role").iter(),
+            Value::parse_json(r#"{"sort": 2, "hidden": true}"#).unwrap(),
+            MergeStrategy::Deep,
+        ));
+        // Merge into an auto-created path
+        assert!(value.merge_at(
+            JsonPointer::parse("settings/display").iter(),
+            Value::parse_json(r#"{"theme": "dark"}"#).unwrap(),
+            MergeStrategy::Deep,
+        ));
+        assert!(value.merge_at(
+            JsonPointer::parse("mailboxes").iter(),
+            Value::parse_json(r#"[{"id": "a", "name": "Inbox"}, {"id": "b"}]"#).unwrap(),
+            MergeStrategy::ArraysByKey(Key::Borrowed("id")),
+        ));
+        assert!(value.merge_at(
+            JsonPointer::parse("").iter(),
+            Value::parse_json(r#"{"n": {"m": 2}}"#).unwrap(),
+            MergeStrategy::Deep,
+        ));
+        assert_eq!(
+            value,
+            Value::parse_json(
+                r#"{"mailboxes": [
+                    {"id": "a", "role": {"name": "inbox", "sort": 2, "hidden": true}, "name": "Inbox"},
+                    {"id": "b"}
+                ], "n": {"m": 2}, "settings": {"display": {"theme": "dark"}}}"#
+            )
+            .unwrap()
+        );
+
+        let expected = value.clone();
+        for pointer in ["mailboxes/5/role", "n/m/x", "new/*/x", "new/**"] {
+            assert!(
+                !value.merge_at(
+                    JsonPointer::parse(pointer).iter(),
+                    Value::Null,
+                    MergeStrategy::Replace,
+                ),
+                "{pointer}"
+            );
+        }
+        assert_eq!(value, expected);
+    }
+
     #[test]
     fn number_test() -> io::Result<()> {
         let data = r#"{"val1": 123.5, "val2": 123, "val3": -123}"#;
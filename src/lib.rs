@@ -6,11 +6,34 @@
 #![doc = include_str!("../README.md")]
 #![deny(rust_2018_idioms)]
 #![forbid(unsafe_code)]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 mod json;
 mod pointer;
 
-pub use json::key::Key;
-pub use json::object_vec::{ObjectAsVec, ObjectAsVec as Map};
-pub use json::value::{Element, Null, Property, Value};
-pub use pointer::{JsonPointer, JsonPointerHandler, JsonPointerItem, JsonPointerIter};
+#[cfg(feature = "fast_hash")]
+pub use json::indexed::FastIndexedObject;
+#[cfg(feature = "std")]
+pub use json::indexed::IndexedObject;
+pub use json::interner::KeyInterner;
+pub use json::key::{CaseInsensitiveKey, Key};
+#[cfg(feature = "raw_number")]
+pub use json::num::RawNumber;
+pub use json::object_vec::{ObjectAsVec, ObjectAsVec as Map, ObjectEntry};
+pub use json::schema::{ObjectSchema, Schema, ValidationError};
+pub use json::ser::{SerializeOptions, SkipNulls, SortedValue};
+pub use json::serializer::to_value;
+#[cfg(feature = "std")]
+pub use json::stream::from_ndjson_reader;
+#[cfg(feature = "std")]
+pub use json::value::CanonicalValue;
+pub use json::value::{
+    ArrayNotation, Element, ElementRepr, FlattenOptions, MergeStrategy, Null, Property, Value,
+};
+pub use pointer::{
+    JsonPointer, JsonPointerBuilder, JsonPointerHandler, JsonPointerItem, JsonPointerIter,
+    JsonPointerMatch, PatchError, PointerParseError, RelativePointer, RelativeTarget,
+    StrictJsonPointer,
+};
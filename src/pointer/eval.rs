@@ -4,13 +4,20 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use super::{JsonPointerHandler, JsonPointerItem};
-use crate::json::key::Key;
-use crate::pointer::JsonPointerIter;
-use crate::{Element, Property, Value};
-use std::borrow::Cow;
-use std::collections::HashMap;
-use std::hash::BuildHasher;
+use super::{JsonPointer, JsonPointerHandler, JsonPointerItem, PatchError};
+use crate::pointer::{JsonPointerIter, JsonPointerMatch};
+use crate::{Element, ObjectAsVec, Property, Value};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use {
+    crate::json::key::Key,
+    alloc::string::{String, ToString},
+    core::hash::BuildHasher,
+    std::collections::HashMap,
+};
 
 impl<'x, P: Property, E: Element> JsonPointerHandler<'x, P, E> for Value<'x, P, E> {
     fn eval_jptr<'y>(
@@ -26,20 +33,22 @@ impl<'x, P: Property, E: Element> JsonPointerHandler<'x, P, E> for Value<'x, P,
                     v.eval_jptr(pointer, results);
                 }
             }
-            Some(JsonPointerItem::Number(n)) => match self {
-                Value::Array(values) => {
-                    if let Some(v) = values.get(*n as usize) {
-                        v.eval_jptr(pointer, results);
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => {
+                        if let Some(v) = item.array_index(values.len()).and_then(|i| values.get(i))
+                        {
+                            v.eval_jptr(pointer, results);
+                        }
                     }
-                }
-                Value::Object(map) => {
-                    let n = Key::Owned(n.to_string());
-                    if let Some(v) = map.get(&n) {
-                        v.eval_jptr(pointer, results);
+                    Value::Object(map) => {
+                        if let Some(v) = item.index_key().and_then(|key| map.get(&key)) {
+                            v.eval_jptr(pointer, results);
+                        }
                     }
+                    _ => {}
                 }
-                _ => {}
-            },
+            }
             Some(JsonPointerItem::Wildcard) => match self {
                 Value::Array(values) => {
                     for v in values {
@@ -53,6 +62,11 @@ impl<'x, P: Property, E: Element> JsonPointerHandler<'x, P, E> for Value<'x, P,
                 }
                 _ => {}
             },
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                let mut matches = Vec::new();
+                self.eval_recursive(pointer, &mut matches);
+                push_unique_matches(matches, results);
+            }
             Some(JsonPointerItem::Root) | None => {
                 results.push(Cow::Borrowed(self));
             }
@@ -80,31 +94,38 @@ impl<'x, P: Property, E: Element> JsonPointerHandler<'x, P, E> for Value<'x, P,
                     }
                 }
             }
-            Some(JsonPointerItem::Number(n)) => match self {
-                Value::Array(values) => {
-                    if let Some(item) = values.get_mut(*n as usize) {
-                        return if pointer.peek().is_some() {
-                            item.patch_jptr(pointer, value)
-                        } else {
-                            *item = value;
-                            true
-                        };
+            Some(index @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => {
+                        if let Some(item) = index
+                            .array_index(values.len())
+                            .and_then(|i| values.get_mut(i))
+                        {
+                            return if pointer.peek().is_some() {
+                                item.patch_jptr(pointer, value)
+                            } else {
+                                *item = value;
+                                true
+                            };
+                        }
                     }
-                }
-                Value::Object(map) => {
-                    let n = Key::Owned(n.to_string());
-                    if let Some(item) = map.get_mut(&n) {
-                        return if pointer.peek().is_some() {
-                            item.patch_jptr(pointer, value)
-                        } else {
-                            *item = value;
-                            true
-                        };
+                    Value::Object(map) => {
+                        if let Some(item) = index.index_key().and_then(|key| map.get_mut(&key)) {
+                            return if pointer.peek().is_some() {
+                                item.patch_jptr(pointer, value)
+                            } else {
+                                *item = value;
+                                true
+                            };
+                        }
                     }
+                    _ => {}
                 }
-                _ => {}
-            },
-            Some(JsonPointerItem::Wildcard) | Some(JsonPointerItem::Root) | None => (),
+            }
+            Some(JsonPointerItem::Wildcard)
+            | Some(JsonPointerItem::RecursiveWildcard)
+            | Some(JsonPointerItem::Root)
+            | None => (),
         }
 
         false
@@ -113,6 +134,410 @@ impl<'x, P: Property, E: Element> JsonPointerHandler<'x, P, E> for Value<'x, P,
     fn to_value<'y>(&'y self) -> Cow<'y, Value<'x, P, E>> {
         Cow::Borrowed(self)
     }
+
+    fn eval_count(&self, mut pointer: JsonPointerIter<'_, P>) -> usize {
+        let current = pointer.clone();
+        match pointer.next() {
+            Some(JsonPointerItem::Key(key)) => match self {
+                Value::Object(map) => map.get(key).map_or(0, |v| v.eval_count(pointer)),
+                _ => 0,
+            },
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => item
+                        .array_index(values.len())
+                        .and_then(|i| values.get(i))
+                        .map_or(0, |v| v.eval_count(pointer)),
+                    Value::Object(map) => item
+                        .index_key()
+                        .and_then(|key| map.get(&key))
+                        .map_or(0, |v| v.eval_count(pointer)),
+                    _ => 0,
+                }
+            }
+            Some(JsonPointerItem::Wildcard) => match self {
+                Value::Array(values) => values.iter().map(|v| v.eval_count(pointer.clone())).sum(),
+                Value::Object(map) => map.values().map(|v| v.eval_count(pointer.clone())).sum(),
+                _ => 0,
+            },
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                // Matches have to be collected in order to be deduplicated
+                let mut results = Vec::new();
+                self.eval_jptr(current, &mut results);
+                results.len()
+            }
+            Some(JsonPointerItem::Root) | None => 1,
+        }
+    }
+
+    fn eval_exists(&self, mut pointer: JsonPointerIter<'_, P>) -> bool {
+        let current = pointer.clone();
+        match pointer.next() {
+            Some(JsonPointerItem::Key(key)) => match self {
+                Value::Object(map) => map.get(key).is_some_and(|v| v.eval_exists(pointer)),
+                _ => false,
+            },
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => item
+                        .array_index(values.len())
+                        .and_then(|i| values.get(i))
+                        .is_some_and(|v| v.eval_exists(pointer)),
+                    Value::Object(map) => item
+                        .index_key()
+                        .and_then(|key| map.get(&key))
+                        .is_some_and(|v| v.eval_exists(pointer)),
+                    _ => false,
+                }
+            }
+            Some(JsonPointerItem::Wildcard) => match self {
+                Value::Array(values) => values.iter().any(|v| v.eval_exists(pointer.clone())),
+                Value::Object(map) => map.values().any(|v| v.eval_exists(pointer.clone())),
+                _ => false,
+            },
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                self.eval_exists(pointer)
+                    || match self {
+                        Value::Array(values) => {
+                            values.iter().any(|v| v.eval_exists(current.clone()))
+                        }
+                        Value::Object(map) => map.values().any(|v| v.eval_exists(current.clone())),
+                        _ => false,
+                    }
+            }
+            Some(JsonPointerItem::Root) | None => true,
+        }
+    }
+}
+
+impl<'x, P: Property, E: Element> Value<'x, P, E> {
+    /// Replaces the value at `pointer`, failing if the pointer does not match the shape of
+    /// the document.
+    ///
+    /// Unlike `patch_jptr`, numeric segments only index into arrays and key segments only
+    /// into objects. As with `patch_jptr`, a missing key is inserted when it is the last
+    /// segment. `Root` segments are skipped, so an empty pointer replaces the whole value.
+    pub fn patch_jptr_strict(
+        &mut self,
+        pointer: &JsonPointer<P>,
+        value: Value<'x, P, E>,
+    ) -> Result<(), PatchError<P>> {
+        self.patch_strict_at(pointer.as_slice(), 0, value)
+    }
+
+    /// Walks `pointer` and returns a mutable reference to the value it points to, creating
+    /// it along the way if missing. Missing intermediate keys are inserted as empty objects and
+    /// a missing final key is inserted as `null`.
+    ///
+    /// Array indices are never created, so a numeric segment that is out of bounds in an array
+    /// returns `None`, as do wildcard segments and segments that traverse a scalar.
+    pub fn get_or_create(
+        &mut self,
+        mut pointer: JsonPointerIter<'_, P>,
+    ) -> Option<&mut Value<'x, P, E>> {
+        let key = match pointer.next() {
+            None => return Some(self),
+            Some(JsonPointerItem::Root) => return self.get_or_create(pointer),
+            Some(JsonPointerItem::Key(key)) => Cow::Borrowed(key),
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => {
+                        return item
+                            .array_index(values.len())
+                            .and_then(|i| values.get_mut(i))
+                            .and_then(|item| item.get_or_create(pointer));
+                    }
+                    _ => Cow::Owned(item.index_key()?),
+                }
+            }
+            Some(JsonPointerItem::Wildcard | JsonPointerItem::RecursiveWildcard) => return None,
+        };
+
+        if let Value::Object(map) = self {
+            let pos = match map.0.iter().position(|(k, _)| *k == *key) {
+                Some(pos) => pos,
+                None => {
+                    let value = if pointer.peek().is_some() {
+                        Value::Object(ObjectAsVec::new())
+                    } else {
+                        Value::Null
+                    };
+                    map.insert_unchecked(key.into_owned(), value);
+                    map.0.len() - 1
+                }
+            };
+            map.0[pos].1.get_or_create(pointer)
+        } else {
+            None
+        }
+    }
+
+    fn patch_strict_at(
+        &mut self,
+        items: &[JsonPointerItem<P>],
+        pos: usize,
+        value: Value<'x, P, E>,
+    ) -> Result<(), PatchError<P>> {
+        let prefix = || JsonPointer(items[..=pos].to_vec());
+        let is_last = pos + 1 >= items.len();
+
+        match (items.get(pos), self) {
+            (None, this) => {
+                *this = value;
+                Ok(())
+            }
+            (Some(JsonPointerItem::Root), this) => this.patch_strict_at(items, pos + 1, value),
+            (Some(JsonPointerItem::Key(key)), Value::Object(map)) => {
+                if let Some(item) = map.get_mut(key) {
+                    item.patch_strict_at(items, pos + 1, value)
+                } else if is_last {
+                    map.insert_unchecked(key.clone(), value);
+                    Ok(())
+                } else {
+                    Err(PatchError::NotFound(prefix()))
+                }
+            }
+            (
+                Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))),
+                Value::Array(values),
+            ) => {
+                if let Some(item) = item
+                    .array_index(values.len())
+                    .and_then(|i| values.get_mut(i))
+                {
+                    item.patch_strict_at(items, pos + 1, value)
+                } else {
+                    Err(PatchError::NotFound(prefix()))
+                }
+            }
+            (Some(JsonPointerItem::Wildcard | JsonPointerItem::RecursiveWildcard), _) => {
+                Err(PatchError::InvalidSegment(prefix()))
+            }
+            (
+                Some(
+                    JsonPointerItem::Key(_)
+                    | JsonPointerItem::Number(_)
+                    | JsonPointerItem::NumberFromEnd(_),
+                ),
+                _,
+            ) => Err(PatchError::TypeMismatch(prefix())),
+        }
+    }
+
+    /// Evaluates `pointer` against this value and every value nested below it.
+    ///
+    /// Each additional `**` segment multiplies the work by the number of
+    /// descendants, so pointers with several recursive wildcards are
+    /// polynomial in the size of the tree.
+    fn eval_recursive<'y>(
+        &'y self,
+        pointer: JsonPointerIter<'_, P>,
+        results: &mut Vec<Cow<'y, Value<'x, P, E>>>,
+    ) {
+        self.eval_jptr(pointer.clone(), results);
+        match self {
+            Value::Array(values) => {
+                for v in values {
+                    v.eval_recursive(pointer.clone(), results);
+                }
+            }
+            Value::Object(map) => {
+                for v in map.values() {
+                    v.eval_recursive(pointer.clone(), results);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluates `pointer` like `eval_jptr`, recording for every match the concrete pointer
+    /// it was found at, with wildcards resolved to the actual keys and indexes. The paths
+    /// can be passed back to `patch_jptr` to modify the matched values.
+    pub fn eval_jptr_with_paths<'y>(
+        &'y self,
+        pointer: JsonPointerIter<'_, P>,
+        results: &mut Vec<JsonPointerMatch<'y, 'x, P, E>>,
+    ) {
+        let mut matches = Vec::new();
+        self.eval_with_paths(pointer, &mut Vec::new(), &mut matches);
+
+        // As with `eval_jptr`, nodes reached more than once through `**` are reported once
+        let mut seen = BTreeSet::new();
+        for (path, value) in matches {
+            if seen.insert(value as *const Value<'x, P, E>) {
+                results.push((path, Cow::Borrowed(value)));
+            }
+        }
+    }
+
+    fn eval_with_paths<'y>(
+        &'y self,
+        mut pointer: JsonPointerIter<'_, P>,
+        path: &mut Vec<JsonPointerItem<P>>,
+        results: &mut Vec<(JsonPointer<P>, &'y Value<'x, P, E>)>,
+    ) {
+        let current = pointer.clone();
+        match pointer.next() {
+            Some(JsonPointerItem::Key(key)) => {
+                if let Value::Object(map) = self
+                    && let Some((k, v)) = map.get_key_value(key)
+                {
+                    path.push(k.to_pointer_item());
+                    v.eval_with_paths(pointer, path, results);
+                    path.pop();
+                }
+            }
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                match self {
+                    Value::Array(values) => {
+                        if let Some(i) = item.array_index(values.len())
+                            && let Some(v) = values.get(i)
+                        {
+                            path.push(JsonPointerItem::Number(i as u64));
+                            v.eval_with_paths(pointer, path, results);
+                            path.pop();
+                        }
+                    }
+                    Value::Object(map) => {
+                        if let Some((k, v)) =
+                            item.index_key().and_then(|key| map.get_key_value(&key))
+                        {
+                            path.push(k.to_pointer_item());
+                            v.eval_with_paths(pointer, path, results);
+                            path.pop();
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some(JsonPointerItem::Wildcard) => {
+                self.eval_children_with_paths(pointer, path, results);
+            }
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                self.eval_with_paths(pointer, path, results);
+                self.eval_children_with_paths(current, path, results);
+            }
+            Some(JsonPointerItem::Root) => {
+                self.eval_with_paths(pointer, path, results);
+            }
+            None => {
+                let path = if path.is_empty() {
+                    JsonPointer(vec![JsonPointerItem::Root])
+                } else {
+                    JsonPointer(path.clone())
+                };
+                results.push((path, self));
+            }
+        }
+    }
+
+    fn eval_children_with_paths<'y>(
+        &'y self,
+        pointer: JsonPointerIter<'_, P>,
+        path: &mut Vec<JsonPointerItem<P>>,
+        results: &mut Vec<(JsonPointer<P>, &'y Value<'x, P, E>)>,
+    ) {
+        match self {
+            Value::Array(values) => {
+                for (idx, v) in values.iter().enumerate() {
+                    path.push(JsonPointerItem::Number(idx as u64));
+                    v.eval_with_paths(pointer.clone(), path, results);
+                    path.pop();
+                }
+            }
+            Value::Object(map) => {
+                for (k, v) in map.iter() {
+                    path.push(k.to_pointer_item());
+                    v.eval_with_paths(pointer.clone(), path, results);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluates `pointer` like `eval_jptr`, calling `f` with a mutable reference to each
+    /// match instead of collecting them, so only one match is borrowed at a time.
+    ///
+    /// Nodes reached more than once through `**` are passed to `f` once. The pointer is
+    /// evaluated top-down and `f` runs as soon as a node matches, so with `**` the search
+    /// below a match sees the changes `f` made to it.
+    pub fn eval_each_mut<F>(&mut self, pointer: JsonPointerIter<'_, P>, mut f: F)
+    where
+        F: FnMut(&mut Value<'x, P, E>),
+    {
+        self.eval_each_mut_at(pointer, &mut BTreeSet::new(), &mut f);
+    }
+
+    fn eval_each_mut_at<F>(
+        &mut self,
+        mut pointer: JsonPointerIter<'_, P>,
+        seen: &mut BTreeSet<*const Value<'x, P, E>>,
+        f: &mut F,
+    ) where
+        F: FnMut(&mut Value<'x, P, E>),
+    {
+        let current = pointer.clone();
+        match pointer.next() {
+            Some(JsonPointerItem::Key(key)) => {
+                if let Value::Object(map) = self
+                    && let Some(v) = map.get_mut(key)
+                {
+                    v.eval_each_mut_at(pointer, seen, f);
+                }
+            }
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                let v = match self {
+                    Value::Array(values) => item
+                        .array_index(values.len())
+                        .and_then(|i| values.get_mut(i)),
+                    Value::Object(map) => item.index_key().and_then(|key| map.get_mut(&key)),
+                    _ => None,
+                };
+                if let Some(v) = v {
+                    v.eval_each_mut_at(pointer, seen, f);
+                }
+            }
+            Some(JsonPointerItem::Wildcard) => {
+                self.eval_children_each_mut(pointer, seen, f);
+            }
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                self.eval_each_mut_at(pointer, seen, f);
+                self.eval_children_each_mut(current, seen, f);
+            }
+            Some(JsonPointerItem::Root) => {
+                self.eval_each_mut_at(pointer, seen, f);
+            }
+            None => {
+                if seen.insert(self as *const Value<'x, P, E>) {
+                    f(self);
+                }
+            }
+        }
+    }
+
+    fn eval_children_each_mut<F>(
+        &mut self,
+        pointer: JsonPointerIter<'_, P>,
+        seen: &mut BTreeSet<*const Value<'x, P, E>>,
+        f: &mut F,
+    ) where
+        F: FnMut(&mut Value<'x, P, E>),
+    {
+        match self {
+            Value::Array(values) => {
+                for v in values {
+                    v.eval_each_mut_at(pointer.clone(), seen, f);
+                }
+            }
+            Value::Object(map) => {
+                for (_, v) in map.iter_mut() {
+                    v.eval_each_mut_at(pointer.clone(), seen, f);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl<'x, P: Property, E: Element, T> JsonPointerHandler<'x, P, E> for Vec<T>
@@ -124,9 +549,10 @@ where
         mut pointer: JsonPointerIter<'_, P>,
         results: &mut Vec<Cow<'y, Value<'x, P, E>>>,
     ) {
+        let current = pointer.clone();
         match pointer.next() {
-            Some(JsonPointerItem::Number(n)) => {
-                if let Some(v) = self.get(*n as usize) {
+            Some(item @ (JsonPointerItem::Number(_) | JsonPointerItem::NumberFromEnd(_))) => {
+                if let Some(v) = item.array_index(self.len()).and_then(|i| self.get(i)) {
                     v.eval_jptr(pointer, results);
                 }
             }
@@ -135,6 +561,15 @@ where
                     v.eval_jptr(pointer.clone(), results);
                 }
             }
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                // Values built by `to_value` are owned and can't be deduplicated
+                let mut matches = Vec::new();
+                self.eval_jptr(pointer, &mut matches);
+                for v in self {
+                    v.eval_jptr(current.clone(), &mut matches);
+                }
+                push_unique_matches(matches, results);
+            }
             Some(JsonPointerItem::Root) | None => {
                 results.push(self.to_value());
             }
@@ -147,8 +582,11 @@ where
         mut pointer: JsonPointerIter<'_, P>,
         value: Value<'y, P, E>,
     ) -> bool {
-        if let Some(JsonPointerItem::Number(n)) = pointer.next()
-            && let Some(item) = self.get_mut(*n as usize)
+        let len = self.len();
+        if let Some(item) = pointer
+            .next()
+            .and_then(|item| item.array_index(len))
+            .and_then(|i| self.get_mut(i))
         {
             if pointer.peek().is_some() {
                 return item.patch_jptr(pointer, value);
@@ -185,6 +623,7 @@ where
     }
 }
 
+#[cfg(feature = "std")]
 impl<'x, P: Property, E: Element, T, S: BuildHasher + Default> JsonPointerHandler<'x, P, E>
     for HashMap<String, T, S>
 where
@@ -195,6 +634,7 @@ where
         mut pointer: JsonPointerIter<'_, P>,
         results: &mut Vec<Cow<'y, Value<'x, P, E>>>,
     ) {
+        let current = pointer.clone();
         match pointer.next() {
             Some(JsonPointerItem::Key(key)) => {
                 if let Some(v) = self.get(key.to_string().as_ref()) {
@@ -202,8 +642,15 @@ where
                 }
             }
             Some(JsonPointerItem::Number(n)) => {
-                let n = n.to_string();
-                if let Some(v) = self.get(&n) {
+                if let Some(v) = self.get(format_index(*n, &mut [0; 20])) {
+                    v.eval_jptr(pointer, results);
+                }
+            }
+            Some(item @ JsonPointerItem::NumberFromEnd(_)) => {
+                if let Some(v) = item
+                    .index_key()
+                    .and_then(|key| self.get(key.to_string().as_ref()))
+                {
                     v.eval_jptr(pointer, results);
                 }
             }
@@ -212,6 +659,15 @@ where
                     v.eval_jptr(pointer.clone(), results);
                 }
             }
+            Some(JsonPointerItem::RecursiveWildcard) => {
+                // Values built by `to_value` are owned and can't be deduplicated
+                let mut matches = Vec::new();
+                self.eval_jptr(pointer, &mut matches);
+                for v in self.values() {
+                    v.eval_jptr(current.clone(), &mut matches);
+                }
+                push_unique_matches(matches, results);
+            }
             Some(JsonPointerItem::Root) | None => {
                 results.push(self.to_value());
             }
@@ -225,8 +681,8 @@ where
     ) -> bool {
         match pointer.next() {
             Some(JsonPointerItem::Key(key)) => {
-                let key = key.to_string();
-                if let Some(item) = self.get_mut(key.as_ref()) {
+                // `Key::to_string` borrows, so only inserting a new key allocates
+                if let Some(item) = self.get_mut(key.to_string().as_ref()) {
                     if pointer.peek().is_some() {
                         return item.patch_jptr(pointer, value);
                     } else if let Ok(value) = T::try_from(value) {
@@ -236,16 +692,27 @@ where
                 } else if pointer.next().is_none()
                     && let Ok(v) = T::try_from(value)
                 {
-                    self.insert(key.into_owned(), v);
+                    self.insert(key.to_string().into_owned(), v);
                     return true;
                 }
             }
             Some(JsonPointerItem::Number(n)) => {
-                if let Some(v) = self.get_mut(&n.to_string()) {
+                if let Some(v) = self.get_mut(format_index(*n, &mut [0; 20])) {
                     return v.patch_jptr(pointer, value);
                 }
             }
-            Some(JsonPointerItem::Wildcard) | Some(JsonPointerItem::Root) | None => (),
+            Some(item @ JsonPointerItem::NumberFromEnd(_)) => {
+                if let Some(v) = item
+                    .index_key()
+                    .and_then(|key| self.get_mut(key.to_string().as_ref()))
+                {
+                    return v.patch_jptr(pointer, value);
+                }
+            }
+            Some(JsonPointerItem::Wildcard)
+            | Some(JsonPointerItem::RecursiveWildcard)
+            | Some(JsonPointerItem::Root)
+            | None => (),
         }
 
         false
@@ -260,6 +727,42 @@ where
     }
 }
 
+/// Appends `matches` to `results`, skipping borrowed values that were already matched. The
+/// same node can be reached through more than one path when a pointer contains several `**`
+/// segments, so borrowed matches are deduplicated by address.
+fn push_unique_matches<'y, 'x, P: Property, E: Element>(
+    matches: Vec<Cow<'y, Value<'x, P, E>>>,
+    results: &mut Vec<Cow<'y, Value<'x, P, E>>>,
+) {
+    let mut seen = BTreeSet::new();
+    for item in matches {
+        if let Cow::Borrowed(v) = &item
+            && !seen.insert(*v as *const Value<'x, P, E>)
+        {
+            continue;
+        }
+        results.push(item);
+    }
+}
+
+/// Formats an index into a stack buffer so numeric segments can be looked up in
+/// string-keyed maps without allocating.
+#[cfg(feature = "std")]
+fn format_index(mut n: u64, buf: &mut [u8; 20]) -> &str {
+    let mut pos = buf.len();
+    loop {
+        pos -= 1;
+        buf[pos] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    // Only ASCII digits were written
+    core::str::from_utf8(&buf[pos..]).unwrap_or_default()
+}
+
+#[cfg(feature = "std")]
 impl<'x, P: Property, E: Element, T> TryFrom<Value<'x, P, E>> for HashMap<String, T>
 where
     T: JsonPointerHandler<'x, P, E> + for<'y> TryFrom<Value<'y, P, E>> + 'static,
@@ -279,11 +782,11 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use crate::{
         Element, JsonPointer, JsonPointerHandler, JsonPointerItem, Key, Null, ObjectAsVec,
-        Property, Value, pointer::JsonPointerIter,
+        PatchError, Property, Value, pointer::JsonPointerIter,
     };
     use serde::{Deserialize, Serialize, Serializer};
     use std::{borrow::Cow, collections::HashMap};
@@ -329,6 +832,479 @@ mod tests {
         test_json_patch(value, "value");
     }
 
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum TestProp {
+        Name,
+        Emails,
+    }
+
+    impl Property for TestProp {
+        fn try_parse(_: Option<&Key<'_, Self>>, value: &str) -> Option<Self> {
+            match value {
+                "name" => Some(TestProp::Name),
+                "emails" => Some(TestProp::Emails),
+                _ => None,
+            }
+        }
+
+        fn to_cow(&self) -> Cow<'static, str> {
+            match self {
+                TestProp::Name => "name".into(),
+                TestProp::Emails => "emails".into(),
+            }
+        }
+    }
+
+    #[test]
+    fn json_pointer_builder() {
+        let value = Value::<'_, TestProp, Null>::Object(ObjectAsVec::from(vec![
+            (Key::Property(TestProp::Name), Value::Str("John".into())),
+            (
+                Key::Property(TestProp::Emails),
+                Value::Array(vec![
+                    Value::Object(ObjectAsVec::from(vec![(
+                        Key::Borrowed("email"),
+                        Value::Str("john@example.org".into()),
+                    )])),
+                    Value::Object(ObjectAsVec::from(vec![(
+                        Key::Borrowed("email"),
+                        Value::Str("jdoe@example.org".into()),
+                    )])),
+                ]),
+            ),
+        ]));
+
+        let ptr = JsonPointer::builder().property(TestProp::Name).build();
+        assert_eq!(
+            ptr.as_slice(),
+            &[JsonPointerItem::Key(Key::Property(TestProp::Name))]
+        );
+        let mut results = Vec::new();
+        value.eval_jptr(ptr.iter(), &mut results);
+        assert_eq!(results, vec![Cow::<Value<'_, _, _>>::Owned("John".into())]);
+
+        let ptr = JsonPointer::builder()
+            .property(TestProp::Emails)
+            .index(1)
+            .key("email")
+            .build();
+        let mut results = Vec::new();
+        value.eval_jptr(ptr.iter(), &mut results);
+        assert_eq!(
+            results,
+            vec![Cow::<Value<'_, _, _>>::Owned("jdoe@example.org".into())]
+        );
+
+        let ptr = JsonPointer::builder()
+            .property(TestProp::Emails)
+            .wildcard()
+            .key("email")
+            .build();
+        let mut results = Vec::new();
+        value.eval_jptr(ptr.iter(), &mut results);
+        assert_eq!(results.len(), 2);
+
+        assert_eq!(
+            JsonPointer::<TestProp>::builder().build(),
+            JsonPointer::parse("")
+        );
+    }
+
+    #[test]
+    fn json_pointer_recursive_wildcard() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{
+                "password": "a",
+                "user": {"password": "b", "name": "c"},
+                "list": [{"password": "d"}, {"other": {"password": "e"}}]
+            }"#,
+        )
+        .unwrap();
+
+        for (pointer, expected) in [
+            ("**/password", r#"["a","b","d","e"]"#),
+            ("list/**/password", r#"["d","e"]"#),
+            ("**/**/password", r#"["a","b","d","e"]"#),
+            ("**/other/*", r#"["e"]"#),
+            ("user/**", r#"["b","c",{"password":"b","name":"c"}]"#),
+        ] {
+            let ptr = JsonPointer::parse(pointer);
+            assert_eq!(ptr.to_string(), pointer);
+            let mut results = Vec::new();
+            value.eval_jptr(ptr.iter(), &mut results);
+            results.sort_unstable_by_key(|a| a.to_string());
+            assert_eq!(
+                serde_json::to_string(&results).unwrap(),
+                expected,
+                "{pointer}"
+            );
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct Leaf(Value<'static, Null, Null>);
+
+    impl JsonPointerHandler<'static, Null, Null> for Leaf {
+        fn eval_jptr<'y>(
+            &'y self,
+            pointer: JsonPointerIter<'_, Null>,
+            results: &mut Vec<Cow<'y, Value<'static, Null, Null>>>,
+        ) {
+            self.0.eval_jptr(pointer, results);
+        }
+
+        fn patch_jptr<'y: 'static>(
+            &mut self,
+            pointer: JsonPointerIter<'_, Null>,
+            value: Value<'y, Null, Null>,
+        ) -> bool {
+            self.0.patch_jptr(pointer, value)
+        }
+
+        fn to_value<'y>(&'y self) -> Cow<'y, Value<'static, Null, Null>> {
+            Cow::Borrowed(&self.0)
+        }
+    }
+
+    impl TryFrom<Value<'_, Null, Null>> for Leaf {
+        type Error = ();
+
+        fn try_from(value: Value<'_, Null, Null>) -> Result<Self, Self::Error> {
+            Ok(Leaf(value.into_owned()))
+        }
+    }
+
+    #[test]
+    fn json_pointer_recursive_wildcard_collections() {
+        let leaf = |json| Leaf(Value::parse_json(json).unwrap().into_owned());
+        let list = vec![leaf(r#"{"id": 1}"#), leaf(r#"{"other": {"id": 2}}"#)];
+        let map = HashMap::from([
+            ("a".to_string(), leaf(r#"{"id": 3}"#)),
+            ("b".to_string(), leaf(r#"[{"id": 4}]"#)),
+        ]);
+
+        for (pointer, list_expected, map_expected) in [
+            ("**/id", "[1,2]", "[3,4]"),
+            ("**/**/id", "[1,2]", "[3,4]"),
+            ("**/**/**/id", "[1,2]", "[3,4]"),
+        ] {
+            let ptr = JsonPointer::<Null>::parse(pointer);
+            for (results, expected) in [
+                (list.eval_owned(ptr.iter()), list_expected),
+                (map.eval_owned(ptr.iter()), map_expected),
+            ] {
+                let mut results = results.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+                results.sort_unstable();
+                assert_eq!(format!("[{}]", results.join(",")), expected, "{pointer}");
+            }
+        }
+    }
+
+    #[test]
+    fn json_pointer_count_exists() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{
+                "list": [{"id": 1, "name": "a"}, {"id": 2}, {"id": 3, "name": "c"}],
+                "nested": {"list": [{"name": "d"}]}
+            }"#,
+        )
+        .unwrap();
+
+        for (pointer, count) in [
+            ("list/*/name", 2),
+            ("list/*/id", 3),
+            ("list/1/name", 0),
+            ("list/*/missing", 0),
+            ("**/name", 3),
+            ("", 1),
+        ] {
+            let ptr = JsonPointer::parse(pointer);
+            let mut results = Vec::new();
+            value.eval_jptr(ptr.iter(), &mut results);
+            assert_eq!(results.len(), count, "{pointer}");
+            assert_eq!(value.eval_count(ptr.iter()), count, "{pointer}");
+            assert_eq!(value.eval_exists(ptr.iter()), count > 0, "{pointer}");
+        }
+    }
+
+    #[test]
+    fn json_pointer_from_end() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{"list": [{"id": 1}, {"id": 2}, {"id": 3}], "map": {"-1": "minus one"}}"#,
+        )
+        .unwrap();
+
+        for (pointer, expected) in [
+            ("list/-1/id", Some("3")),
+            ("list/-3/id", Some("1")),
+            ("list/-4/id", None),
+            ("map/-1", Some(r#""minus one""#)),
+            ("list/-", None),
+        ] {
+            let ptr = JsonPointer::parse_extended(pointer);
+            let mut results = Vec::new();
+            value.eval_jptr(ptr.iter(), &mut results);
+            assert_eq!(
+                results.first().map(|v| v.to_string()),
+                expected.map(String::from),
+                "{pointer}"
+            );
+            assert_eq!(
+                value.eval_exists(ptr.iter()),
+                expected.is_some(),
+                "{pointer}"
+            );
+        }
+
+        let mut patched = value.clone();
+        assert!(patched.patch_jptr(
+            JsonPointer::parse_extended("list/-2/id").iter(),
+            Value::Number(20u64.into())
+        ));
+        assert!(!patched.patch_jptr(
+            JsonPointer::parse_extended("list/-4/id").iter(),
+            Value::Number(40u64.into())
+        ));
+        assert_eq!(
+            patched.to_string(),
+            r#"{"list":[{"id":1},{"id":20},{"id":3}],"map":{"-1":"minus one"}}"#
+        );
+
+        let mut results = Vec::new();
+        value.eval_jptr_with_paths(JsonPointer::parse_extended("list/-1").iter(), &mut results);
+        assert_eq!(results[0].0, JsonPointer::parse("list/2"));
+    }
+
+    #[test]
+    fn json_pointer_eval_owned() {
+        let json = r#"{"list": [{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]}"#;
+        let value = Value::<'_, Null, Null>::parse_json(json).unwrap();
+
+        let names = value.eval_owned(JsonPointer::parse("list/*/name").iter());
+        let ids = value.eval_owned(JsonPointer::parse("list/*/id").iter());
+        drop(value);
+
+        assert_eq!(names, vec![Value::Str("a".into()), Value::Str("b".into())]);
+        assert_eq!(
+            ids.iter().map(|v| v.as_u64()).collect::<Vec<_>>(),
+            vec![Some(1), Some(2)]
+        );
+    }
+
+    #[test]
+    fn json_pointer_patch_strict() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{"list": [{"id": 1}], "map": {"0": "zero"}, "text": "abc"}"#,
+        )
+        .unwrap();
+
+        for (pointer, expected) in [
+            (
+                "list/0/id",
+                Ok(r#"{"list":[{"id":2}],"map":{"0":"zero"},"text":"abc"}"#),
+            ),
+            (
+                "list/0/name",
+                Ok(r#"{"list":[{"id":1,"name":2}],"map":{"0":"zero"},"text":"abc"}"#),
+            ),
+            (
+                "map/new",
+                Ok(r#"{"list":[{"id":1}],"map":{"0":"zero","new":2},"text":"abc"}"#),
+            ),
+            ("", Ok("2")),
+            (
+                "map/0",
+                Err(PatchError::TypeMismatch(JsonPointer::parse("map/0"))),
+            ),
+            (
+                "list/id",
+                Err(PatchError::TypeMismatch(JsonPointer::parse("list/id"))),
+            ),
+            (
+                "text/a",
+                Err(PatchError::TypeMismatch(JsonPointer::parse("text/a"))),
+            ),
+            (
+                "list/1/id",
+                Err(PatchError::NotFound(JsonPointer::parse("list/1"))),
+            ),
+            (
+                "other/id",
+                Err(PatchError::NotFound(JsonPointer::parse("other"))),
+            ),
+            (
+                "list/*/id",
+                Err(PatchError::InvalidSegment(JsonPointer::parse("list/*"))),
+            ),
+        ] {
+            let mut value = value.clone();
+            let result = value
+                .patch_jptr_strict(&JsonPointer::parse(pointer), Value::Number(2u64.into()))
+                .map(|_| value.to_string());
+            assert_eq!(result, expected.map(String::from), "{pointer}");
+        }
+    }
+
+    #[test]
+    fn json_pointer_eval_with_paths() {
+        let mut value = Value::<'_, Null, Null>::parse_json(
+            r#"{"map": {"key1": {"text": "value1"}, "key2": {"text": "value2"}, "key3": {}},
+                "list": [{"text": "item1"}, {"nested": {"text": "item2"}}]}"#,
+        )
+        .unwrap();
+
+        let eval = |value: &Value<'_, Null, Null>, pointer: &str| {
+            let mut results = Vec::new();
+            value.eval_jptr_with_paths(JsonPointer::parse(pointer).iter(), &mut results);
+            results
+                .into_iter()
+                .map(|(path, v)| (path.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            eval(&value, "map/*/text"),
+            vec![
+                ("map/key1/text".to_string(), r#""value1""#.to_string()),
+                ("map/key2/text".to_string(), r#""value2""#.to_string()),
+            ]
+        );
+        assert_eq!(
+            eval(&value, "**/text"),
+            vec![
+                ("map/key1/text".to_string(), r#""value1""#.to_string()),
+                ("map/key2/text".to_string(), r#""value2""#.to_string()),
+                ("list/0/text".to_string(), r#""item1""#.to_string()),
+                ("list/1/nested/text".to_string(), r#""item2""#.to_string()),
+            ]
+        );
+        assert_eq!(eval(&value, "**/**/text").len(), 4);
+        assert_eq!(eval(&value, "list/1").len(), 1);
+        assert_eq!(eval(&value, "")[0].0, "");
+        assert!(eval(&value, "map/key3/text").is_empty());
+
+        for pointer in ["map/*/text", "**/text", "list/0", "list/*/nested"] {
+            let mut results = Vec::new();
+            value.eval_jptr(JsonPointer::parse(pointer).iter(), &mut results);
+            assert_eq!(
+                results
+                    .into_iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>(),
+                eval(&value, pointer)
+                    .into_iter()
+                    .map(|(_, v)| v)
+                    .collect::<Vec<_>>(),
+                "{pointer}"
+            );
+        }
+
+        let mut results = Vec::new();
+        value.eval_jptr_with_paths(JsonPointer::parse("map/*/text").iter(), &mut results);
+        let paths = results
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        for path in paths {
+            assert!(value.patch_jptr(path.iter(), Value::Str("patched".into())));
+        }
+        assert_eq!(
+            eval(&value, "map/*/text")
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>(),
+            vec![r#""patched""#, r#""patched""#]
+        );
+    }
+
+    #[test]
+    fn json_pointer_eval_each_mut() {
+        let value = Value::<'_, Null, Null>::parse_json(
+            r#"{
+                "list": [{"id": 1, "n": 10}, {"id": 2}, {"id": 3, "n": 30}],
+                "nested": {"n": 100, "deep": {"n": 1000}}
+            }"#,
+        )
+        .unwrap();
+
+        for (pointer, expected) in [
+            (
+                "list/*/n",
+                r#"{"list":[{"id":1,"n":11},{"id":2},{"id":3,"n":31}],"nested":{"deep":{"n":1000},"n":100}}"#,
+            ),
+            (
+                "**/n",
+                r#"{"list":[{"id":1,"n":11},{"id":2},{"id":3,"n":31}],"nested":{"deep":{"n":1001},"n":101}}"#,
+            ),
+            (
+                "**/**/n",
+                r#"{"list":[{"id":1,"n":11},{"id":2},{"id":3,"n":31}],"nested":{"deep":{"n":1001},"n":101}}"#,
+            ),
+            (
+                "list/-1/id",
+                r#"{"list":[{"id":1,"n":10},{"id":2},{"id":4,"n":30}],"nested":{"deep":{"n":1000},"n":100}}"#,
+            ),
+            (
+                "list/5/id",
+                r#"{"list":[{"id":1,"n":10},{"id":2},{"id":3,"n":30}],"nested":{"deep":{"n":1000},"n":100}}"#,
+            ),
+        ] {
+            let mut value = value.clone();
+            let mut calls = 0;
+            value.eval_each_mut(JsonPointer::parse_extended(pointer).iter(), |v| {
+                calls += 1;
+                if let Some(n) = v.as_u64() {
+                    *v = Value::Number((n + 1).into());
+                }
+            });
+            assert_eq!(value.to_string(), expected, "{pointer}");
+
+            let mut results = Vec::new();
+            value.eval_jptr(JsonPointer::parse_extended(pointer).iter(), &mut results);
+            assert_eq!(calls, results.len(), "{pointer}");
+        }
+    }
+
+    #[test]
+    fn json_pointer_get_or_create() {
+        let mut value = Value::<'_, Null, Null>::parse_json(r#"{"list": [{"id": 1}]}"#).unwrap();
+
+        *value
+            .get_or_create(JsonPointer::parse("a/b/c").iter())
+            .unwrap() = Value::Number(2u64.into());
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":{"b":{"c":2}},"list":[{"id":1}]}"#
+        );
+
+        let slot = value
+            .get_or_create(JsonPointer::parse("list/0/name").iter())
+            .unwrap();
+        assert_eq!(slot, &Value::Null);
+        assert_eq!(
+            value
+                .get_or_create(JsonPointer::parse("a/b/c").iter())
+                .unwrap(),
+            &Value::Number(2u64.into())
+        );
+
+        for pointer in ["list/1/id", "list/*", "a/b/c/d"] {
+            assert!(
+                value
+                    .get_or_create(JsonPointer::parse(pointer).iter())
+                    .is_none(),
+                "{pointer}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_index() {
+        for n in [0, 7, 10, 1234567890, u64::MAX] {
+            assert_eq!(super::format_index(n, &mut [0; 20]), n.to_string());
+        }
+    }
+
     fn test_json_pointer<T, P, E>(obj: &T, test: &str)
     where
         T: JsonPointerHandler<'static, P, E>,
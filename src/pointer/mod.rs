@@ -6,10 +6,21 @@
 
 pub(crate) mod eval;
 pub(crate) mod parser;
+pub(crate) mod relative;
+
+pub use parser::PointerParseError;
+pub use relative::{RelativePointer, RelativeTarget};
 
 use crate::{Element, Key, Property, Value};
-use std::{
+use alloc::{
     borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
     fmt::{Debug, Display, Formatter},
     iter::Peekable,
     slice::Iter,
@@ -27,6 +38,26 @@ pub trait JsonPointerHandler<'x, P: Property, E: Element>: Debug {
         value: Value<'y, P, E>,
     ) -> bool;
     fn to_value<'y>(&'y self) -> Cow<'y, Value<'x, P, E>>;
+
+    /// Returns the number of values matched by the pointer.
+    fn eval_count(&self, pointer: JsonPointerIter<'_, P>) -> usize {
+        let mut results = Vec::new();
+        self.eval_jptr(pointer, &mut results);
+        results.len()
+    }
+
+    /// Returns true if the pointer matches at least one value.
+    fn eval_exists(&self, pointer: JsonPointerIter<'_, P>) -> bool {
+        self.eval_count(pointer) > 0
+    }
+
+    /// Evaluates the pointer like `eval_jptr`, returning the matches as owned values that
+    /// are no longer tied to the borrow of `self`. Borrowed matches are cloned.
+    fn eval_owned(&self, pointer: JsonPointerIter<'_, P>) -> Vec<Value<'x, P, E>> {
+        let mut results = Vec::new();
+        self.eval_jptr(pointer, &mut results);
+        results.into_iter().map(Cow::into_owned).collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -34,19 +65,73 @@ pub struct JsonPointer<P: Property>(pub(crate) Vec<JsonPointerItem<P>>);
 
 pub type JsonPointerIter<'x, P> = Peekable<Iter<'x, JsonPointerItem<P>>>;
 
+/// A value matched by `Value::eval_jptr_with_paths`, along with its concrete location.
+pub type JsonPointerMatch<'y, 'x, P, E> = (JsonPointer<P>, Cow<'y, Value<'x, P, E>>);
+
+/// A pointer that is deserialized with [`JsonPointer::parse_rfc6901`], rejecting input that
+/// the lenient parser would silently accept, and serialized in RFC 6901 form.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StrictJsonPointer<P: Property>(pub JsonPointer<P>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPointerBuilder<P: Property>(Vec<JsonPointerItem<P>>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum JsonPointerItem<P: Property> {
     Root,
     Wildcard,
+    RecursiveWildcard,
     Key(Key<'static, P>),
     Number(u64),
+    /// An array index counted from the end, where `1` is the last element. Only produced by
+    /// [`JsonPointer::parse_extended`].
+    NumberFromEnd(u64),
+}
+
+/// Error returned by [`Value::patch_jptr_strict`], carrying the pointer prefix up to and
+/// including the segment where the patch diverged from the document shape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError<P: Property> {
+    /// The segment does not apply to the value type, such as an index into an object or a key
+    /// into an array.
+    TypeMismatch(JsonPointer<P>),
+    /// The key or index does not exist in the target container.
+    NotFound(JsonPointer<P>),
+    /// Wildcard segments cannot be patched.
+    InvalidSegment(JsonPointer<P>),
+}
+
+impl<P: Property> PatchError<P> {
+    pub fn pointer(&self) -> &JsonPointer<P> {
+        match self {
+            PatchError::TypeMismatch(pointer)
+            | PatchError::NotFound(pointer)
+            | PatchError::InvalidSegment(pointer) => pointer,
+        }
+    }
+}
+
+impl<P: Property> Display for PatchError<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PatchError::TypeMismatch(pointer) => write!(f, "type mismatch at '{pointer}'"),
+            PatchError::NotFound(pointer) => write!(f, "path not found at '{pointer}'"),
+            PatchError::InvalidSegment(pointer) => write!(f, "invalid segment at '{pointer}'"),
+        }
+    }
 }
 
+impl<P: Property> core::error::Error for PatchError<P> {}
+
 impl<P: Property> JsonPointer<P> {
     pub fn new(items: Vec<JsonPointerItem<P>>) -> Self {
         Self(items)
     }
 
+    pub fn builder() -> JsonPointerBuilder<P> {
+        JsonPointerBuilder(Vec::new())
+    }
+
     pub fn iter(&self) -> JsonPointerIter<'_, P> {
         self.0.iter().peekable()
     }
@@ -82,6 +167,24 @@ impl<P: Property> JsonPointer<P> {
         encoded
     }
 
+    /// Encodes pointer items into a string, producing the same output as `Display`: numbers
+    /// are written as digits, wildcards as `*` and `**`, `Root` is skipped and keys are
+    /// escaped.
+    pub fn encode_items(items: &[JsonPointerItem<P>]) -> String {
+        let mut encoded = String::with_capacity(items.len() * 8);
+        let _ = write_items(&mut encoded, items);
+        encoded
+    }
+
+    /// Formats the pointer in RFC 6901 form, as accepted by [`JsonPointer::parse_rfc6901`]:
+    /// every segment is preceded by `/` and the root pointer is the empty string. Wildcards
+    /// have no meaning in RFC 6901 and are written as literal `*` and `**` keys.
+    pub fn to_rfc6901_string(&self) -> String {
+        let mut encoded = String::with_capacity(self.0.len() * 8);
+        let _ = write_rfc6901_items(&mut encoded, &self.0);
+        encoded
+    }
+
     pub fn first(&self) -> Option<&JsonPointerItem<P>> {
         self.0.first()
     }
@@ -113,6 +216,109 @@ impl<P: Property> JsonPointer<P> {
     pub fn as_mut_slice(&mut self) -> &mut [JsonPointerItem<P>] {
         &mut self.0
     }
+
+    /// Returns the pointer in its canonical form: leading `Root` segments are removed when
+    /// other segments follow, and a pointer without segments becomes `[Root]`. Pointers
+    /// produced by `parse` and the builder are already normalized, so comparing or hashing
+    /// normalized pointers is equivalent to comparing the locations they address.
+    pub fn normalized(&self) -> JsonPointer<P> {
+        let items = self
+            .0
+            .iter()
+            .skip_while(|item| matches!(item, JsonPointerItem::Root))
+            .cloned()
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            JsonPointer(vec![JsonPointerItem::Root])
+        } else {
+            JsonPointer(items)
+        }
+    }
+
+    /// Returns the longest leading portion shared by both pointers, in normalized form.
+    /// Segments are compared structurally, so wildcards only match identical wildcards and a
+    /// numeric segment does not match a key with the same digits. Returns the root pointer
+    /// if nothing is shared.
+    pub fn common_prefix(&self, other: &JsonPointer<P>) -> JsonPointer<P> {
+        let is_root = |item: &&JsonPointerItem<P>| matches!(item, JsonPointerItem::Root);
+        let mut items = self
+            .0
+            .iter()
+            .skip_while(is_root)
+            .zip(other.0.iter().skip_while(is_root))
+            .take_while(|(a, b)| a == b)
+            .map(|(item, _)| item.clone())
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            items.push(JsonPointerItem::Root);
+        }
+        JsonPointer(items)
+    }
+
+    /// Returns true if this pointer, used as a query pattern, would match the literal path
+    /// `concrete`. `*` matches exactly one segment and `**` matches zero or more segments,
+    /// following the evaluation rules; numeric segments match keys with the same digits.
+    pub fn matches_path(&self, concrete: &JsonPointer<P>) -> bool {
+        matches_segments(&self.0, &concrete.0)
+    }
+}
+
+fn matches_segments<P: Property>(
+    query: &[JsonPointerItem<P>],
+    path: &[JsonPointerItem<P>],
+) -> bool {
+    if let Some((JsonPointerItem::Root, path)) = path.split_first() {
+        return matches_segments(query, path);
+    }
+
+    match query.split_first() {
+        None => path.is_empty(),
+        Some((JsonPointerItem::Root, query)) => matches_segments(query, path),
+        Some((JsonPointerItem::RecursiveWildcard, query)) => {
+            (0..=path.len()).any(|pos| matches_segments(query, &path[pos..]))
+        }
+        Some((item, query)) => match path.split_first() {
+            Some((segment, path)) => item.matches_segment(segment) && matches_segments(query, path),
+            None => false,
+        },
+    }
+}
+
+impl<P: Property> JsonPointerBuilder<P> {
+    pub fn property(mut self, property: P) -> Self {
+        self.0.push(JsonPointerItem::Key(Key::Property(property)));
+        self
+    }
+
+    pub fn key(mut self, key: &str) -> Self {
+        self.0
+            .push(JsonPointerItem::Key(Key::Owned(key.to_string())));
+        self
+    }
+
+    pub fn index(mut self, index: u64) -> Self {
+        self.0.push(JsonPointerItem::Number(index));
+        self
+    }
+
+    pub fn wildcard(mut self) -> Self {
+        self.0.push(JsonPointerItem::Wildcard);
+        self
+    }
+
+    pub fn recursive_wildcard(mut self) -> Self {
+        self.0.push(JsonPointerItem::RecursiveWildcard);
+        self
+    }
+
+    /// Builds the pointer. An empty builder yields a pointer to the root, matching
+    /// `JsonPointer::parse("")`.
+    pub fn build(mut self) -> JsonPointer<P> {
+        if self.0.is_empty() {
+            self.0.push(JsonPointerItem::Root);
+        }
+        JsonPointer(self.0)
+    }
 }
 
 impl<P: Property> JsonPointerItem<P> {
@@ -123,6 +329,13 @@ impl<P: Property> JsonPointerItem<P> {
         }
     }
 
+    pub fn into_key(self) -> Option<Key<'static, P>> {
+        match self {
+            JsonPointerItem::Key(key) => Some(key),
+            _ => None,
+        }
+    }
+
     pub fn as_property_key(&self) -> Option<&P> {
         match self {
             JsonPointerItem::Key(Key::Property(key)) => Some(key),
@@ -134,42 +347,170 @@ impl<P: Property> JsonPointerItem<P> {
         match self {
             JsonPointerItem::Key(Key::Borrowed(key)) => Some(key),
             JsonPointerItem::Key(Key::Owned(key)) => Some(key),
+            JsonPointerItem::Key(Key::Shared(key)) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Resolves a numeric segment against an array of `len` elements. Returns `None` for
+    /// other segments and for indexes counted from the end that precede the first element.
+    pub(crate) fn array_index(&self, len: usize) -> Option<usize> {
+        match self {
+            JsonPointerItem::Number(n) => usize::try_from(*n).ok(),
+            JsonPointerItem::NumberFromEnd(n) => len.checked_sub(usize::try_from(*n).ok()?),
+            _ => None,
+        }
+    }
+
+    /// Returns the object key addressed by a numeric segment, which is its string form.
+    pub(crate) fn index_key(&self) -> Option<Key<'static, P>> {
+        match self {
+            JsonPointerItem::Number(n) => Some(Key::Owned(n.to_string())),
+            JsonPointerItem::NumberFromEnd(n) => Some(Key::Owned(format!("-{n}"))),
             _ => None,
         }
     }
 
+    fn rank(&self) -> u8 {
+        match self {
+            JsonPointerItem::Root => 0,
+            JsonPointerItem::Wildcard => 1,
+            JsonPointerItem::RecursiveWildcard => 2,
+            JsonPointerItem::Key(_) => 3,
+            JsonPointerItem::Number(_) => 4,
+            JsonPointerItem::NumberFromEnd(_) => 5,
+        }
+    }
+
+    fn matches_segment(&self, segment: &JsonPointerItem<P>) -> bool {
+        match (self, segment) {
+            (JsonPointerItem::Wildcard, _) => true,
+            (JsonPointerItem::Key(a), JsonPointerItem::Key(b)) => a == b,
+            (JsonPointerItem::Number(a), JsonPointerItem::Number(b)) => a == b,
+            (JsonPointerItem::NumberFromEnd(a), JsonPointerItem::NumberFromEnd(b)) => a == b,
+            (JsonPointerItem::Key(key), index @ JsonPointerItem::Number(_))
+            | (index @ JsonPointerItem::Number(_), JsonPointerItem::Key(key)) => {
+                index.index_key().as_ref() == Some(key)
+            }
+            _ => false,
+        }
+    }
+
     pub fn to_cow(&self) -> Option<Cow<'_, str>> {
         match self {
             JsonPointerItem::Key(Key::Property(key)) => Some(key.to_cow()),
             JsonPointerItem::Key(Key::Borrowed(key)) => Some(Cow::Borrowed(key)),
             JsonPointerItem::Key(Key::Owned(key)) => Some(Cow::Borrowed(key)),
+            JsonPointerItem::Key(Key::Shared(key)) => Some(Cow::Borrowed(key)),
             _ => None,
         }
     }
 }
 
-impl<P: Property> Display for JsonPointer<P> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for (i, ptr) in self.0.iter().enumerate() {
-            if i > 0 {
-                write!(f, "/")?;
+/// Items are ordered by variant, `Root < Wildcard < RecursiveWildcard < Key < Number <
+/// NumberFromEnd`, then by value. Keys compare by their string form, see [`Key`].
+impl<P: Property> Ord for JsonPointerItem<P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (JsonPointerItem::Key(a), JsonPointerItem::Key(b)) => a.cmp(b),
+            (JsonPointerItem::Number(a), JsonPointerItem::Number(b))
+            | (JsonPointerItem::NumberFromEnd(a), JsonPointerItem::NumberFromEnd(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl<P: Property> PartialOrd for JsonPointerItem<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: Property> From<Key<'static, P>> for JsonPointerItem<P> {
+    fn from(key: Key<'static, P>) -> Self {
+        JsonPointerItem::Key(key)
+    }
+}
+
+impl<P: Property> StrictJsonPointer<P> {
+    pub fn into_inner(self) -> JsonPointer<P> {
+        self.0
+    }
+}
+
+impl<P: Property> From<StrictJsonPointer<P>> for JsonPointer<P> {
+    fn from(pointer: StrictJsonPointer<P>) -> Self {
+        pointer.0
+    }
+}
+
+impl<P: Property> Display for StrictJsonPointer<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write_rfc6901_items(f, self.0.as_slice())
+    }
+}
+
+fn write_rfc6901_items<P: Property>(
+    f: &mut impl core::fmt::Write,
+    items: &[JsonPointerItem<P>],
+) -> core::fmt::Result {
+    for item in items {
+        match item {
+            JsonPointerItem::Root => continue,
+            JsonPointerItem::Wildcard => f.write_str("/*")?,
+            JsonPointerItem::RecursiveWildcard => f.write_str("/**")?,
+            JsonPointerItem::Key(k) => {
+                f.write_char('/')?;
+                for c in k.to_string().chars() {
+                    match c {
+                        '~' => f.write_str("~0")?,
+                        '/' => f.write_str("~1")?,
+                        _ => f.write_char(c)?,
+                    }
+                }
             }
+            JsonPointerItem::Number(n) => write!(f, "/{}", n)?,
+            JsonPointerItem::NumberFromEnd(n) => write!(f, "/-{}", n)?,
+        }
+    }
+    Ok(())
+}
+
+impl<P: Property> Display for JsonPointer<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write_items(f, &self.0)
+    }
+}
+
+fn write_items<P: Property>(
+    f: &mut impl core::fmt::Write,
+    items: &[JsonPointerItem<P>],
+) -> core::fmt::Result {
+    for (i, ptr) in items
+        .iter()
+        .filter(|item| !matches!(item, JsonPointerItem::Root))
+        .enumerate()
+    {
+        if i > 0 {
+            f.write_char('/')?;
+        }
 
-            match ptr {
-                JsonPointerItem::Root => {}
-                JsonPointerItem::Wildcard => write!(f, "*")?,
-                JsonPointerItem::Key(k) => {
-                    for c in k.to_string().chars() {
-                        match c {
-                            '~' => write!(f, "~0")?,
-                            '/' => write!(f, "~1")?,
-                            _ => write!(f, "{}", c)?,
-                        }
+        match ptr {
+            JsonPointerItem::Root => {}
+            JsonPointerItem::Wildcard => f.write_char('*')?,
+            JsonPointerItem::RecursiveWildcard => f.write_str("**")?,
+            JsonPointerItem::Key(k) => {
+                for c in k.to_string().chars() {
+                    match c {
+                        '~' => f.write_str("~0")?,
+                        '/' => f.write_str("~1")?,
+                        _ => f.write_char(c)?,
                     }
                 }
-                JsonPointerItem::Number(n) => write!(f, "{}", n)?,
             }
+            JsonPointerItem::Number(n) => write!(f, "{}", n)?,
+            JsonPointerItem::NumberFromEnd(n) => write!(f, "-{}", n)?,
         }
-        Ok(())
     }
+    Ok(())
 }
@@ -4,13 +4,25 @@
  * SPDX-License-Identifier: Apache-2.0 OR MIT
  */
 
-use crate::{JsonPointer, JsonPointerItem, Key, Property};
+use crate::{JsonPointer, JsonPointerItem, Key, Property, StrictJsonPointer};
+use alloc::borrow::Cow;
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::convert::Infallible;
+use core::fmt::{Display, Formatter};
+use core::marker::PhantomData;
+use core::str::FromStr;
+use serde::de::{self, MapAccess, Visitor};
 
 enum TokenType {
     Unknown,
     Number,
     String,
     Wildcard,
+    RecursiveWildcard,
     Escaped,
 }
 
@@ -20,64 +32,238 @@ struct State<P: Property> {
     token: TokenType,
     start_pos: usize,
     path: Vec<JsonPointerItem<P>>,
+    strict: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerParseError {
+    /// A `~` at the given byte offset is not followed by `0` or `1`.
+    InvalidEscape { offset: usize },
+    /// The pointer has more segments than allowed.
+    TooManySegments { limit: usize },
+    /// A non-empty pointer does not start with `/`.
+    MissingLeadingSlash,
+    /// The pointer is not valid UTF-8, starting at the given byte offset or within the
+    /// segment starting at it.
+    InvalidUtf8 { offset: usize },
+    /// A relative pointer does not start with a non-negative integer.
+    InvalidRelativePrefix,
+}
+
+impl Display for PointerParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PointerParseError::InvalidEscape { offset } => {
+                write!(f, "invalid escape sequence at offset {offset}")
+            }
+            PointerParseError::TooManySegments { limit } => {
+                write!(f, "pointer exceeds the maximum of {limit} segments")
+            }
+            PointerParseError::MissingLeadingSlash => {
+                write!(f, "pointer must be empty or start with '/'")
+            }
+            PointerParseError::InvalidUtf8 { offset } => {
+                write!(f, "pointer is not valid UTF-8 at offset {offset}")
+            }
+            PointerParseError::InvalidRelativePrefix => {
+                write!(f, "relative pointer must start with a non-negative integer")
+            }
+        }
+    }
+}
+
+impl core::error::Error for PointerParseError {}
+
 impl<P: Property> JsonPointer<P> {
+    /// Parses a pointer leniently. Segments that unescape to invalid UTF-8 are converted
+    /// lossily, with replacement characters.
     pub fn parse(value: &str) -> Self {
-        let mut state = State {
+        let mut state = State::new();
+        let _ = state.scan(value.as_bytes(), true, usize::MAX);
+        state.into_pointer()
+    }
+
+    /// Parses a pointer like [`JsonPointer::parse`], additionally turning segments such as
+    /// `-1` or `-2` into indexes counted from the end of an array, where `-1` is the last
+    /// element. A lone `-` is left as a key, as are `-0` and segments with leading zeros.
+    pub fn parse_extended(value: &str) -> Self {
+        let mut pointer = Self::parse(value);
+        for item in &mut pointer.0 {
+            if let JsonPointerItem::Key(Key::Owned(key)) = item
+                && let Some(digits) = key.strip_prefix('-')
+                && !digits.starts_with('0')
+                && digits.bytes().all(|ch| ch.is_ascii_digit())
+                && let Ok(n) = digits.parse::<u64>()
+            {
+                *item = JsonPointerItem::NumberFromEnd(n);
+            }
+        }
+        pointer
+    }
+
+    /// Parses a pointer from untrusted input, failing as soon as the number of segments
+    /// exceeds `max_segments` or if a segment unescapes to invalid UTF-8.
+    pub fn parse_limited(value: &str, max_segments: usize) -> Result<Self, PointerParseError> {
+        let mut state = State::new();
+        state.strict = true;
+        state.scan(value.as_bytes(), true, max_segments)?;
+        Ok(state.into_pointer())
+    }
+
+    /// Parses a pointer following RFC 6901 strictly: a non-empty pointer has to start with
+    /// `/`, `~` is only valid as part of `~0` or `~1`, and `*` and `\` have no special
+    /// meaning. The empty pointer refers to the whole document.
+    pub fn parse_rfc6901(value: &str) -> Result<Self, PointerParseError> {
+        if value.is_empty() {
+            return Ok(JsonPointer(vec![JsonPointerItem::Root]));
+        }
+        let Some(value) = value.strip_prefix('/') else {
+            return Err(PointerParseError::MissingLeadingSlash);
+        };
+
+        let mut path: Vec<JsonPointerItem<P>> = Vec::new();
+        let mut offset = 1;
+        for segment in value.split('/') {
+            let mut token = String::with_capacity(segment.len());
+            let mut chars = segment.char_indices();
+            while let Some((pos, ch)) = chars.next() {
+                if ch == '~' {
+                    match chars.next() {
+                        Some((_, '0')) => token.push('~'),
+                        Some((_, '1')) => token.push('/'),
+                        _ => {
+                            return Err(PointerParseError::InvalidEscape {
+                                offset: offset + pos,
+                            });
+                        }
+                    }
+                } else {
+                    token.push(ch);
+                }
+            }
+            offset += segment.len() + 1;
+
+            let is_index = !token.is_empty()
+                && token.bytes().all(|ch| ch.is_ascii_digit())
+                && (token == "0" || !token.starts_with('0'));
+            let item = match P::try_parse(path.last().and_then(|item| item.as_key()), &token) {
+                Some(prop) => JsonPointerItem::Key(Key::Property(prop)),
+                None => match token.parse::<u64>() {
+                    Ok(n) if is_index => JsonPointerItem::Number(n),
+                    _ => JsonPointerItem::Key(Key::Owned(token)),
+                },
+            };
+            path.push(item);
+        }
+
+        Ok(JsonPointer(path))
+    }
+}
+
+impl<P: Property> JsonPointerItem<P> {
+    /// Parses a single segment with the same rules as [`JsonPointer::parse`]: `~0` and `~1`
+    /// are unescaped, `*` and `**` become wildcards, digits become a `Number` and other
+    /// segments become a `Key`, typed with `P::try_parse`. A `/` in `value` is taken
+    /// literally rather than starting a new segment.
+    pub fn parse_segment(value: &str) -> Self {
+        let mut state = State::new();
+        let _ = state.scan(value.as_bytes(), false, usize::MAX);
+        state
+            .path
+            .pop()
+            .unwrap_or_else(|| JsonPointerItem::Key(Key::Borrowed("")))
+    }
+}
+
+impl<P: Property> FromStr for JsonPointerItem<P> {
+    type Err = Infallible;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Ok(JsonPointerItem::parse_segment(value))
+    }
+}
+
+impl<P: Property> State<P> {
+    fn new() -> Self {
+        State {
             num: 0,
             buf: Vec::new(),
             token: TokenType::Unknown,
             start_pos: 0,
             path: Vec::new(),
-        };
-        let value = value.as_bytes();
+            strict: false,
+        }
+    }
+
+    fn into_pointer(mut self) -> JsonPointer<P> {
+        if self.path.is_empty() {
+            self.path.push(JsonPointerItem::Root);
+        }
+        JsonPointer(self.path)
+    }
+
+    /// Tokenizes `value`, pushing the typed segments to `path`. When `split` is false, `/`
+    /// is taken literally and the whole input is read as a single segment.
+    fn scan(
+        &mut self,
+        value: &[u8],
+        split: bool,
+        max_segments: usize,
+    ) -> Result<(), PointerParseError> {
         let mut iter = value.iter().enumerate();
 
         while let Some((pos, &ch)) = iter.next() {
-            match (ch, &state.token) {
+            match (ch, &self.token) {
                 (b'0'..=b'9', TokenType::Unknown | TokenType::Number) => {
-                    state.num = state
+                    self.num = self
                         .num
                         .saturating_mul(10)
                         .saturating_add((ch - b'0') as u64);
-                    state.token = TokenType::Number;
+                    self.token = TokenType::Number;
                 }
                 (b'*', TokenType::Unknown) => {
-                    state.token = TokenType::Wildcard;
+                    self.token = TokenType::Wildcard;
+                }
+                (b'*', TokenType::Wildcard) => {
+                    self.token = TokenType::RecursiveWildcard;
                 }
                 (b'0', TokenType::Escaped) => {
-                    state.buf.push(b'~');
-                    state.token = TokenType::String;
+                    self.buf.push(b'~');
+                    self.token = TokenType::String;
                 }
                 (b'1', TokenType::Escaped) => {
-                    state.buf.push(b'/');
-                    state.token = TokenType::String;
+                    self.buf.push(b'/');
+                    self.token = TokenType::String;
                 }
-                (b'/', _) => {
-                    state.process(&value[state.start_pos..pos]);
-                    state.token = TokenType::Unknown;
-                    state.start_pos = pos + 1;
+                (b'/', _) if split => {
+                    self.process(&value[self.start_pos..pos])?;
+                    if self.path.len() > max_segments {
+                        return Err(PointerParseError::TooManySegments {
+                            limit: max_segments,
+                        });
+                    }
+                    self.token = TokenType::Unknown;
+                    self.start_pos = pos + 1;
                 }
                 (_, _) => {
-                    if matches!(&state.token, TokenType::Number | TokenType::Wildcard)
-                        && pos > state.start_pos
+                    if matches!(
+                        &self.token,
+                        TokenType::Number | TokenType::Wildcard | TokenType::RecursiveWildcard
+                    ) && pos > self.start_pos
                     {
-                        state
-                            .buf
-                            .extend_from_slice(value.get(state.start_pos..pos).unwrap_or_default());
+                        self.buf
+                            .extend_from_slice(value.get(self.start_pos..pos).unwrap_or_default());
                     }
 
-                    state.token = match ch {
-                        b'~' if !matches!(&state.token, TokenType::Escaped) => TokenType::Escaped,
+                    self.token = match ch {
+                        b'~' if !matches!(&self.token, TokenType::Escaped) => TokenType::Escaped,
                         b'\\' => {
-                            state
-                                .buf
+                            self.buf
                                 .push(iter.next().map(|(_, &ch)| ch).unwrap_or(b'\\'));
                             TokenType::String
                         }
                         _ => {
-                            state.buf.push(ch);
+                            self.buf.push(ch);
                             TokenType::String
                         }
                     };
@@ -85,35 +271,42 @@ impl<P: Property> JsonPointer<P> {
             }
         }
 
-        state.process(value.get(state.start_pos..).unwrap_or_default());
-
-        if state.path.is_empty() {
-            state.path.push(JsonPointerItem::Root);
+        self.process(value.get(self.start_pos..).unwrap_or_default())?;
+        if self.path.len() > max_segments {
+            return Err(PointerParseError::TooManySegments {
+                limit: max_segments,
+            });
         }
 
-        JsonPointer(state.path)
+        Ok(())
     }
-}
 
-impl<P: Property> State<P> {
-    pub fn process(&mut self, token_bytes: &[u8]) {
+    pub fn process(&mut self, token_bytes: &[u8]) -> Result<(), PointerParseError> {
         match self.token {
             TokenType::String => {
-                let item = std::str::from_utf8(&self.buf).unwrap_or_default();
-                match P::try_parse(self.path.last().and_then(|item| item.as_key()), item) {
+                let item = match core::str::from_utf8(&self.buf) {
+                    Ok(item) => Cow::Borrowed(item),
+                    Err(_) if self.strict => {
+                        return Err(PointerParseError::InvalidUtf8 {
+                            offset: self.start_pos,
+                        });
+                    }
+                    Err(_) => String::from_utf8_lossy(&self.buf),
+                };
+                match P::try_parse(self.path.last().and_then(|item| item.as_key()), &item) {
                     Some(prop) => {
                         self.path.push(JsonPointerItem::Key(Key::Property(prop)));
                     }
                     None => {
                         self.path
-                            .push(JsonPointerItem::Key(Key::Owned(item.to_string())));
+                            .push(JsonPointerItem::Key(Key::Owned(item.into_owned())));
                     }
                 }
 
                 self.buf.clear();
             }
             TokenType::Number => {
-                let item = std::str::from_utf8(token_bytes).unwrap_or_default();
+                let item = core::str::from_utf8(token_bytes).unwrap_or_default();
                 match P::try_parse(self.path.last().and_then(|item| item.as_key()), item) {
                     Some(prop) => {
                         self.path.push(JsonPointerItem::Key(Key::Property(prop)));
@@ -131,11 +324,15 @@ impl<P: Property> State<P> {
             TokenType::Wildcard => {
                 self.path.push(JsonPointerItem::Wildcard);
             }
+            TokenType::RecursiveWildcard => {
+                self.path.push(JsonPointerItem::RecursiveWildcard);
+            }
             TokenType::Unknown if self.start_pos > 0 => {
                 self.path.push(JsonPointerItem::Key("".into()));
             }
             _ => (),
         }
+        Ok(())
     }
 }
 
@@ -157,10 +354,170 @@ impl<P: Property> serde::Serialize for JsonPointer<P> {
     }
 }
 
+impl<'de, P: Property> serde::Deserialize<'de> for StrictJsonPointer<P> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(StrictJsonPointerVisitor(PhantomData))
+    }
+}
+
+impl<P: Property> serde::Serialize for StrictJsonPointer<P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+struct StrictJsonPointerVisitor<P: Property>(PhantomData<P>);
+
+impl<P: Property> Visitor<'_> for StrictJsonPointerVisitor<P> {
+    type Value = StrictJsonPointer<P>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("an RFC 6901 JSON pointer")
+    }
+
+    fn visit_str<ERR>(self, value: &str) -> Result<Self::Value, ERR>
+    where
+        ERR: de::Error,
+    {
+        JsonPointer::parse_rfc6901(value)
+            .map(StrictJsonPointer)
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_bytes<ERR>(self, value: &[u8]) -> Result<Self::Value, ERR>
+    where
+        ERR: de::Error,
+    {
+        core::str::from_utf8(value)
+            .map_err(|err| {
+                de::Error::custom(PointerParseError::InvalidUtf8 {
+                    offset: err.valid_up_to(),
+                })
+            })
+            .and_then(|value| self.visit_str(value))
+    }
+}
+
+/// Items are serialized using a tagged representation: `null` for `Root`, `"*"` for
+/// `Wildcard`, `"**"` for `RecursiveWildcard`, `{"key": "..."}` for keys,
+/// `{"number": 3}` for indexes and `{"numberFromEnd": 1}` for indexes counted from
+/// the end. Keys are deserialized with `Property::try_parse`
+/// without a parent key.
+impl<P: Property> serde::Serialize for JsonPointerItem<P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        match self {
+            JsonPointerItem::Root => serializer.serialize_unit(),
+            JsonPointerItem::Wildcard => serializer.serialize_str("*"),
+            JsonPointerItem::RecursiveWildcard => serializer.serialize_str("**"),
+            JsonPointerItem::Key(key) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("key", key)?;
+                map.end()
+            }
+            JsonPointerItem::Number(n) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("number", n)?;
+                map.end()
+            }
+            JsonPointerItem::NumberFromEnd(n) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("numberFromEnd", n)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de, P: Property> serde::Deserialize<'de> for JsonPointerItem<P> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(JsonPointerItemVisitor(PhantomData))
+    }
+}
+
+struct JsonPointerItemVisitor<P: Property>(PhantomData<P>);
+
+impl<'de, P: Property> Visitor<'de> for JsonPointerItemVisitor<P> {
+    type Value = JsonPointerItem<P>;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> core::fmt::Result {
+        formatter.write_str("null, \"*\", \"**\" or an object with a \"key\", \"number\" or \"numberFromEnd\" field")
+    }
+
+    fn visit_unit<ERR>(self) -> Result<Self::Value, ERR>
+    where
+        ERR: de::Error,
+    {
+        Ok(JsonPointerItem::Root)
+    }
+
+    fn visit_none<ERR>(self) -> Result<Self::Value, ERR>
+    where
+        ERR: de::Error,
+    {
+        Ok(JsonPointerItem::Root)
+    }
+
+    fn visit_str<ERR>(self, value: &str) -> Result<Self::Value, ERR>
+    where
+        ERR: de::Error,
+    {
+        match value {
+            "*" => Ok(JsonPointerItem::Wildcard),
+            "**" => Ok(JsonPointerItem::RecursiveWildcard),
+            _ => Err(de::Error::invalid_value(de::Unexpected::Str(value), &self)),
+        }
+    }
+
+    fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let item = match map.next_key::<Cow<'de, str>>()?.as_deref() {
+            Some("key") => {
+                let key = map.next_value::<Cow<'de, str>>()?;
+                JsonPointerItem::Key(match P::try_parse(None, &key) {
+                    Some(prop) => Key::Property(prop),
+                    None => Key::Owned(key.into_owned()),
+                })
+            }
+            Some("number") => JsonPointerItem::Number(map.next_value()?),
+            Some("numberFromEnd") => JsonPointerItem::NumberFromEnd(map.next_value()?),
+            Some(field) => {
+                return Err(de::Error::unknown_field(
+                    field,
+                    &["key", "number", "numberFromEnd"],
+                ));
+            }
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+
+        Ok(item)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use super::{JsonPointer, JsonPointerItem};
+    use super::{JsonPointer, JsonPointerItem, PointerParseError, State};
+    use crate::StrictJsonPointer;
     use crate::{Key, Null, Property};
     use std::borrow::Cow;
 
@@ -189,13 +546,21 @@ mod tests {
         }
     }
 
-    #[test]
-    fn json_pointer_parse() {
-        for (input, output) in vec![
+    fn json_pointer_parse_cases() -> Vec<(&'static str, Vec<JsonPointerItem<Null>>)> {
+        vec![
             ("hello", vec![JsonPointerItem::<Null>::Key("hello".into())]),
             ("9a", vec![JsonPointerItem::Key("9a".into())]),
             ("a9", vec![JsonPointerItem::Key("a9".into())]),
             ("*a", vec![JsonPointerItem::Key("*a".into())]),
+            ("**a", vec![JsonPointerItem::Key("**a".into())]),
+            ("***", vec![JsonPointerItem::Key("***".into())]),
+            (
+                "**/password",
+                vec![
+                    JsonPointerItem::RecursiveWildcard,
+                    JsonPointerItem::Key("password".into()),
+                ],
+            ),
             (
                 "/hello/world",
                 vec![
@@ -261,11 +626,51 @@ mod tests {
                 ],
             ),
             ("", vec![JsonPointerItem::Root]),
-        ] {
+        ]
+    }
+
+    #[test]
+    fn json_pointer_parse() {
+        for (input, output) in json_pointer_parse_cases() {
             assert_eq!(JsonPointer::parse(input).0, output, "{input}");
         }
     }
 
+    #[test]
+    fn json_pointer_parse_segment() {
+        for (input, output) in json_pointer_parse_cases() {
+            if output == [JsonPointerItem::Root] {
+                continue;
+            }
+            let input = input.strip_prefix('/').unwrap_or(input);
+            assert_eq!(
+                input
+                    .split('/')
+                    .map(JsonPointerItem::parse_segment)
+                    .collect::<Vec<_>>(),
+                output,
+                "{input}"
+            );
+        }
+
+        assert_eq!(
+            JsonPointerItem::<Null>::parse_segment(""),
+            JsonPointerItem::Key("".into())
+        );
+        assert_eq!(
+            JsonPointerItem::<Null>::parse_segment("a/b"),
+            JsonPointerItem::Key("a/b".into())
+        );
+        assert_eq!(
+            "**".parse::<JsonPointerItem<Null>>(),
+            Ok(JsonPointerItem::RecursiveWildcard)
+        );
+        assert_eq!(
+            JsonPointerItem::<TestProp>::parse_segment("ids"),
+            JsonPointerItem::Key(Key::Property(TestProp::Ids))
+        );
+    }
+
     #[test]
     fn json_pointer_parse_promotes_digit() {
         let pointer = JsonPointer::<TestProp>::parse("ids/2");
@@ -334,4 +739,393 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn json_pointer_parse_extended() {
+        let pointer = JsonPointer::<TestProp>::parse_extended("list/-1/-/-0/-01/-12/ids/-3");
+        assert_eq!(
+            pointer.0,
+            vec![
+                JsonPointerItem::Key(Key::Owned("list".to_string())),
+                JsonPointerItem::NumberFromEnd(1),
+                JsonPointerItem::Key(Key::Owned("-".to_string())),
+                JsonPointerItem::Key(Key::Owned("-0".to_string())),
+                JsonPointerItem::Key(Key::Owned("-01".to_string())),
+                JsonPointerItem::NumberFromEnd(12),
+                JsonPointerItem::Key(Key::Property(TestProp::Ids)),
+                JsonPointerItem::Key(Key::Property(TestProp::Id("-3".to_string()))),
+            ]
+        );
+        assert_eq!(pointer.to_string(), "list/-1/-/-0/-01/-12/ids/-3");
+        assert_eq!(
+            JsonPointer::<TestProp>::parse("list/-1").0[1],
+            JsonPointerItem::Key(Key::Owned("-1".to_string()))
+        );
+    }
+
+    #[test]
+    fn json_pointer_parse_limited() {
+        let input = "/a".repeat(5000);
+
+        assert_eq!(
+            JsonPointer::<Null>::parse_limited(&input, 4999),
+            Err(PointerParseError::TooManySegments { limit: 4999 })
+        );
+        assert_eq!(
+            JsonPointer::<Null>::parse_limited(&input, 100),
+            Err(PointerParseError::TooManySegments { limit: 100 })
+        );
+        assert_eq!(
+            JsonPointer::<Null>::parse_limited(&input, 5000)
+                .unwrap()
+                .len(),
+            5000
+        );
+        assert_eq!(
+            JsonPointer::<Null>::parse_limited("", 0),
+            Ok(JsonPointer(vec![JsonPointerItem::Root]))
+        );
+    }
+
+    #[test]
+    fn json_pointer_invalid_utf8() {
+        // A `\` escape pushes the following byte on its own, which can leave a segment that is
+        // not valid UTF-8 when scanning raw bytes.
+        let input = b"a/b\\\xff/c";
+
+        let mut state = State::<Null>::new();
+        state.strict = true;
+        assert_eq!(
+            state.scan(input, true, usize::MAX),
+            Err(PointerParseError::InvalidUtf8 { offset: 2 })
+        );
+
+        let mut state = State::<Null>::new();
+        state.scan(input, true, usize::MAX).unwrap();
+        assert_eq!(
+            state.into_pointer(),
+            JsonPointer(vec![
+                JsonPointerItem::Key(Key::Owned("a".to_string())),
+                JsonPointerItem::Key(Key::Owned("b\u{FFFD}".to_string())),
+                JsonPointerItem::Key(Key::Owned("c".to_string())),
+            ])
+        );
+
+        let mut state = State::<Null>::new();
+        state.strict = true;
+        state.scan("a/\\é".as_bytes(), true, usize::MAX).unwrap();
+        assert_eq!(
+            state.into_pointer(),
+            JsonPointer::parse_limited("a/é", usize::MAX).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_pointer_item_serde() {
+        for (item, expected) in [
+            (JsonPointerItem::Root, "null"),
+            (JsonPointerItem::Wildcard, r#""*""#),
+            (JsonPointerItem::RecursiveWildcard, r#""**""#),
+            (JsonPointerItem::Number(3), r#"{"number":3}"#),
+            (
+                JsonPointerItem::Key(Key::Owned("a/b".to_string())),
+                r#"{"key":"a/b"}"#,
+            ),
+            (
+                JsonPointerItem::Key(Key::Property(TestProp::Ids)),
+                r#"{"key":"ids"}"#,
+            ),
+        ] {
+            let json = serde_json::to_string(&item).unwrap();
+            assert_eq!(json, expected);
+            assert_eq!(
+                serde_json::from_str::<JsonPointerItem<TestProp>>(&json).unwrap(),
+                item
+            );
+        }
+
+        for invalid in [
+            r#""a""#,
+            r#"{"other":1}"#,
+            r#"{"key":"a","number":1}"#,
+            "{}",
+        ] {
+            assert!(
+                serde_json::from_str::<JsonPointerItem<TestProp>>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn json_pointer_matches_path() {
+        let matches = |query: &str, path: &str| {
+            JsonPointer::<Null>::parse(query).matches_path(&JsonPointer::parse(path))
+        };
+
+        // Single wildcard
+        assert!(matches("a/*/c", "a/b/c"));
+        assert!(matches("a/*/c", "a/0/c"));
+        assert!(!matches("a/*/c", "a/b/d"));
+
+        // Trailing wildcard
+        assert!(matches("a/*", "a/b"));
+        assert!(!matches("a/*", "a"));
+        assert!(!matches("a/*", "a/b/c"));
+
+        // Recursive wildcard
+        assert!(matches("a/**", "a"));
+        assert!(matches("a/**/c", "a/b/x/c"));
+        assert!(!matches("a/**/c", "a/b/x/d"));
+
+        // Non-matching lengths
+        assert!(!matches("a/b", "a/b/c"));
+        assert!(!matches("a/b/c", "a/b"));
+        assert!(matches("", ""));
+        assert!(!matches("", "a"));
+        assert!(matches("a/1", "a/1"));
+    }
+
+    #[test]
+    fn json_pointer_parse_rfc6901() {
+        for (input, output) in [
+            ("", vec![JsonPointerItem::Root]),
+            ("/", vec![JsonPointerItem::Key("".into())]),
+            (
+                "/a~1b/~0c/0/01/*",
+                vec![
+                    JsonPointerItem::Key("a/b".into()),
+                    JsonPointerItem::Key("~c".into()),
+                    JsonPointerItem::Number(0),
+                    JsonPointerItem::Key("01".into()),
+                    JsonPointerItem::Key("*".into()),
+                ],
+            ),
+            (
+                "/a\\b/ ",
+                vec![
+                    JsonPointerItem::Key("a\\b".into()),
+                    JsonPointerItem::Key(" ".into()),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                JsonPointer::<Null>::parse_rfc6901(input),
+                Ok(JsonPointer(output)),
+                "{input}"
+            );
+        }
+
+        assert_eq!(
+            JsonPointer::<TestProp>::parse_rfc6901("/ids/2").unwrap().0,
+            vec![
+                JsonPointerItem::Key(Key::Property(TestProp::Ids)),
+                JsonPointerItem::Key(Key::Property(TestProp::Id("2".to_string()))),
+            ]
+        );
+
+        for (input, err) in [
+            ("a/b", PointerParseError::MissingLeadingSlash),
+            ("/a~", PointerParseError::InvalidEscape { offset: 2 }),
+            ("/ab/c~2", PointerParseError::InvalidEscape { offset: 5 }),
+        ] {
+            assert_eq!(
+                JsonPointer::<Null>::parse_rfc6901(input),
+                Err(err),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn json_pointer_to_rfc6901_string() {
+        for (items, expected) in [
+            (vec![JsonPointerItem::Root], ""),
+            (
+                vec![
+                    JsonPointerItem::Key("a".into()),
+                    JsonPointerItem::Key("b".into()),
+                ],
+                "/a/b",
+            ),
+            (
+                vec![
+                    JsonPointerItem::Key("a/b".into()),
+                    JsonPointerItem::Number(0),
+                    JsonPointerItem::Key("~c".into()),
+                    JsonPointerItem::Key("".into()),
+                ],
+                "/a~1b/0/~0c/",
+            ),
+        ] {
+            let pointer = JsonPointer::<Null>(items);
+            let encoded = pointer.to_rfc6901_string();
+            assert_eq!(encoded, expected);
+            assert_eq!(
+                JsonPointer::parse_rfc6901(&encoded),
+                Ok(pointer),
+                "{expected}"
+            );
+        }
+
+        assert_eq!(
+            JsonPointer::<Null>(vec![
+                JsonPointerItem::Root,
+                JsonPointerItem::Key("a".into())
+            ])
+            .to_rfc6901_string(),
+            "/a"
+        );
+
+        let pointer = JsonPointer::<Null>::parse("a/*/**");
+        assert_eq!(pointer.to_string(), "a/*/**");
+        assert_eq!(pointer.to_rfc6901_string(), "/a/*/**");
+        assert_eq!(
+            JsonPointer::<Null>::parse_rfc6901(&pointer.to_rfc6901_string())
+                .unwrap()
+                .0,
+            vec![
+                JsonPointerItem::Key("a".into()),
+                JsonPointerItem::Key("*".into()),
+                JsonPointerItem::Key("**".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_json_pointer_serde() {
+        let pointer: StrictJsonPointer<Null> = serde_json::from_str(r#""/a~1b/0/~0""#).unwrap();
+        assert_eq!(
+            pointer.0,
+            JsonPointer(vec![
+                JsonPointerItem::Key("a/b".into()),
+                JsonPointerItem::Number(0),
+                JsonPointerItem::Key("~".into()),
+            ])
+        );
+        assert_eq!(serde_json::to_string(&pointer).unwrap(), r#""/a~1b/0/~0""#);
+        assert_eq!(
+            serde_json::to_string(&StrictJsonPointer(JsonPointer::<Null>::parse(""))).unwrap(),
+            r#""""#
+        );
+
+        for invalid in [r#""a/b""#, r#""/a~2""#] {
+            assert!(
+                serde_json::from_str::<StrictJsonPointer<Null>>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn json_pointer_normalized() {
+        assert_eq!(JsonPointer::<Null>::parse(""), JsonPointer::parse(""));
+
+        for (items, expected) in [
+            (vec![], ""),
+            (vec![JsonPointerItem::Root], ""),
+            (vec![JsonPointerItem::Root, JsonPointerItem::Root], ""),
+            (
+                vec![JsonPointerItem::Root, JsonPointerItem::Key("a".into())],
+                "a",
+            ),
+            (
+                vec![
+                    JsonPointerItem::Key("a".into()),
+                    JsonPointerItem::Number(1),
+                    JsonPointerItem::Wildcard,
+                ],
+                "a/1/*",
+            ),
+        ] {
+            let normalized = JsonPointer::<Null>::new(items).normalized();
+            assert_eq!(normalized, JsonPointer::parse(expected), "{expected}");
+            assert_eq!(normalized.normalized(), normalized, "{expected}");
+        }
+
+        for input in ["", "/", "a/b", "a/0/**", "~0/~1"] {
+            let pointer = JsonPointer::<Null>::parse(input);
+            assert_eq!(pointer.normalized(), pointer, "{input}");
+        }
+
+        let pointers = [
+            JsonPointer::<Null>::new(vec![]).normalized(),
+            JsonPointer::new(vec![JsonPointerItem::Root]).normalized(),
+            JsonPointer::parse(""),
+        ]
+        .into_iter()
+        .collect::<std::collections::HashSet<_>>();
+        assert_eq!(pointers.len(), 1);
+    }
+
+    #[test]
+    fn json_pointer_common_prefix() {
+        for (a, b, expected) in [
+            ("a/b/c", "a/b/c", "a/b/c"),
+            ("a/b/c", "a/b/d/e", "a/b"),
+            ("a/b", "a/b/c", "a/b"),
+            ("list/0/id", "list/1/id", "list"),
+            ("a/*/c", "a/*/d", "a/*"),
+            ("a/*/c", "a/b/c", "a"),
+            ("a/b", "c/b", ""),
+            ("", "a/b", ""),
+            ("", "", ""),
+        ] {
+            let (a, b) = (JsonPointer::<Null>::parse(a), JsonPointer::parse(b));
+            assert_eq!(a.common_prefix(&b), JsonPointer::parse(expected), "{a} {b}");
+            assert_eq!(b.common_prefix(&a), JsonPointer::parse(expected), "{a} {b}");
+        }
+
+        let rooted = JsonPointer::<Null>(vec![
+            JsonPointerItem::Root,
+            JsonPointerItem::Key("a".into()),
+            JsonPointerItem::Key("b".into()),
+        ]);
+        assert_eq!(
+            rooted.common_prefix(&JsonPointer::parse("a/c")),
+            JsonPointer::parse("a")
+        );
+        assert_eq!(
+            rooted.common_prefix(&JsonPointer::parse("c")).0,
+            vec![JsonPointerItem::Root]
+        );
+    }
+
+    #[test]
+    fn json_pointer_encode() {
+        assert_eq!(
+            JsonPointer::<Null>::encode(["a", "b/c", "d~e", ""]),
+            "a/b~1c/d~0e/"
+        );
+        assert_eq!(JsonPointer::<Null>::encode(Vec::<String>::new()), "");
+
+        for items in [
+            vec![JsonPointerItem::Root],
+            vec![
+                JsonPointerItem::Key("a/b".into()),
+                JsonPointerItem::Number(3),
+                JsonPointerItem::Wildcard,
+                JsonPointerItem::Key("~c".into()),
+                JsonPointerItem::RecursiveWildcard,
+                JsonPointerItem::Key("01".into()),
+            ],
+            vec![
+                JsonPointerItem::Key("a".into()),
+                JsonPointerItem::Key("".into()),
+                JsonPointerItem::Number(0),
+            ],
+        ] {
+            let encoded = JsonPointer::<Null>::encode_items(&items);
+            assert_eq!(encoded, JsonPointer(items.clone()).to_string());
+            assert_eq!(JsonPointer::<Null>::parse(&encoded).into_inner(), items);
+        }
+
+        assert_eq!(
+            JsonPointer::<Null>::encode_items(&[
+                JsonPointerItem::Root,
+                JsonPointerItem::Key("a".into())
+            ]),
+            "a"
+        );
+    }
 }
@@ -0,0 +1,223 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs LLC <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR MIT
+ */
+
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+use crate::{JsonPointer, JsonPointerItem, PointerParseError, Property, StrictJsonPointer};
+
+/// A Relative JSON Pointer (draft-handrews-relative-json-pointer), such as `0/foo`, `2/a/1`
+/// or `1#`, resolved against the absolute location of the current value.
+///
+/// It is kept separate from [`JsonPointer`] because the two grammars differ: the leading
+/// integer is a number of levels to walk up, not a key or index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RelativePointer<P: Property> {
+    /// Number of levels to walk up from the current value.
+    pub levels: u64,
+    pub target: RelativeTarget<P>,
+}
+
+/// What a [`RelativePointer`] refers to once the prefix has been applied.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RelativeTarget<P: Property> {
+    /// A JSON pointer evaluated from the ancestor value.
+    Pointer(JsonPointer<P>),
+    /// The `#` suffix: the key or index under which the ancestor is stored in its parent.
+    KeyOrIndex,
+}
+
+impl<P: Property> RelativePointer<P> {
+    /// Parses a relative pointer: a non-negative integer without leading zeros, followed by
+    /// either `#` or a pointer in RFC 6901 form.
+    pub fn parse(value: &str) -> Result<Self, PointerParseError> {
+        let prefix_len = value
+            .bytes()
+            .position(|ch| !ch.is_ascii_digit())
+            .unwrap_or(value.len());
+        let (prefix, suffix) = value.split_at(prefix_len);
+        if prefix.is_empty() || (prefix.len() > 1 && prefix.starts_with('0')) {
+            return Err(PointerParseError::InvalidRelativePrefix);
+        }
+        let levels = prefix
+            .parse()
+            .map_err(|_| PointerParseError::InvalidRelativePrefix)?;
+
+        let target = if suffix == "#" {
+            RelativeTarget::KeyOrIndex
+        } else {
+            RelativeTarget::Pointer(JsonPointer::parse_rfc6901(suffix).map_err(
+                |err| match err {
+                    PointerParseError::InvalidEscape { offset } => {
+                        PointerParseError::InvalidEscape {
+                            offset: offset + prefix_len,
+                        }
+                    }
+                    err => err,
+                },
+            )?)
+        };
+
+        Ok(RelativePointer { levels, target })
+    }
+
+    /// Returns the absolute location addressed by the pointer when evaluated from `base`.
+    /// Returns None if the pointer walks up past the root, or if it ends with `#`, since it
+    /// then refers to a key or index rather than to a location, see
+    /// [`RelativePointer::resolve_key`].
+    pub fn resolve(&self, base: &JsonPointer<P>) -> Option<JsonPointer<P>> {
+        let RelativeTarget::Pointer(pointer) = &self.target else {
+            return None;
+        };
+        let mut items = self.ancestor(base)?;
+        items.extend(
+            pointer
+                .as_slice()
+                .iter()
+                .filter(|item| !matches!(item, JsonPointerItem::Root))
+                .cloned(),
+        );
+        Some(JsonPointer::new(items).normalized())
+    }
+
+    /// Returns the key or index addressed by a pointer ending with `#` when evaluated from
+    /// `base`. Returns None for other pointers, or if the ancestor is the root, which is not
+    /// stored under any key.
+    pub fn resolve_key(&self, base: &JsonPointer<P>) -> Option<JsonPointerItem<P>> {
+        match self.target {
+            RelativeTarget::KeyOrIndex => self.ancestor(base)?.pop(),
+            RelativeTarget::Pointer(_) => None,
+        }
+    }
+
+    fn ancestor(&self, base: &JsonPointer<P>) -> Option<Vec<JsonPointerItem<P>>> {
+        let mut items = base
+            .as_slice()
+            .iter()
+            .filter(|item| !matches!(item, JsonPointerItem::Root))
+            .cloned()
+            .collect::<Vec<_>>();
+        let levels = usize::try_from(self.levels).ok()?;
+        items.truncate(items.len().checked_sub(levels)?);
+        Some(items)
+    }
+}
+
+impl<P: Property> Display for RelativePointer<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.levels)?;
+        match &self.target {
+            RelativeTarget::Pointer(pointer) => {
+                write!(f, "{}", StrictJsonPointer(pointer.clone()))
+            }
+            RelativeTarget::KeyOrIndex => f.write_str("#"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RelativePointer, RelativeTarget};
+    use crate::{JsonPointer, JsonPointerItem, Null, PointerParseError, Value};
+
+    #[test]
+    fn relative_pointer_parse() {
+        for (input, levels, target) in [
+            ("0", 0, Some("")),
+            ("1/0", 1, Some("/0")),
+            ("2/highly/nested/objects", 2, Some("/highly/nested/objects")),
+            ("10/a~1b", 10, Some("/a~1b")),
+            ("0#", 0, None),
+            ("3#", 3, None),
+        ] {
+            let pointer = RelativePointer::<Null>::parse(input).unwrap();
+            assert_eq!(pointer.levels, levels, "{input}");
+            assert_eq!(
+                pointer.target,
+                match target {
+                    Some(target) =>
+                        RelativeTarget::Pointer(JsonPointer::parse_rfc6901(target).unwrap()),
+                    None => RelativeTarget::KeyOrIndex,
+                },
+                "{input}"
+            );
+            assert_eq!(pointer.to_string(), input);
+        }
+
+        for (input, err) in [
+            ("", PointerParseError::InvalidRelativePrefix),
+            ("/foo", PointerParseError::InvalidRelativePrefix),
+            ("01/foo", PointerParseError::InvalidRelativePrefix),
+            ("#", PointerParseError::InvalidRelativePrefix),
+            ("0foo", PointerParseError::MissingLeadingSlash),
+            ("1##", PointerParseError::MissingLeadingSlash),
+            ("0#/foo", PointerParseError::MissingLeadingSlash),
+            ("2/a~2", PointerParseError::InvalidEscape { offset: 3 }),
+        ] {
+            assert_eq!(RelativePointer::<Null>::parse(input), Err(err), "{input}");
+        }
+    }
+
+    #[test]
+    fn relative_pointer_resolve() {
+        // Examples from draft-handrews-relative-json-pointer, section 5.1
+        let document = Value::<'_, Null, Null>::parse_json(
+            r#"{"foo": ["bar", "baz"], "highly": {"nested": {"objects": true}}}"#,
+        )
+        .unwrap();
+
+        for (base, relative, expected) in [
+            ("/foo/1", "0", r#""baz""#),
+            ("/foo/1", "1/0", r#""bar""#),
+            ("/foo/1", "2/highly/nested/objects", "true"),
+            ("/highly/nested", "0/objects", "true"),
+            ("/highly/nested", "1/nested/objects", "true"),
+            ("/highly/nested", "2/foo/0", r#""bar""#),
+        ] {
+            let base = JsonPointer::parse_rfc6901(base).unwrap();
+            let pointer = RelativePointer::parse(relative)
+                .unwrap()
+                .resolve(&base)
+                .unwrap();
+            assert_eq!(
+                document.resolve_pointer(&pointer).unwrap().to_string(),
+                expected,
+                "{relative}"
+            );
+        }
+
+        for (base, relative, expected) in [
+            ("/foo/1", "0#", JsonPointerItem::Number(1)),
+            ("/foo/1", "1#", JsonPointerItem::Key("foo".into())),
+            (
+                "/highly/nested",
+                "0#",
+                JsonPointerItem::Key("nested".into()),
+            ),
+            (
+                "/highly/nested",
+                "1#",
+                JsonPointerItem::Key("highly".into()),
+            ),
+        ] {
+            let base = JsonPointer::parse_rfc6901(base).unwrap();
+            let pointer = RelativePointer::<Null>::parse(relative).unwrap();
+            assert_eq!(pointer.resolve_key(&base), Some(expected), "{relative}");
+            assert_eq!(pointer.resolve(&base), None, "{relative}");
+        }
+
+        let base = JsonPointer::<Null>::parse_rfc6901("/foo").unwrap();
+        for relative in ["2", "2/foo", "1#", "5#"] {
+            let pointer = RelativePointer::parse(relative).unwrap();
+            assert_eq!(pointer.resolve(&base), None, "{relative}");
+            assert_eq!(pointer.resolve_key(&base), None, "{relative}");
+        }
+        assert_eq!(
+            RelativePointer::parse("1").unwrap().resolve(&base),
+            Some(JsonPointer::parse(""))
+        );
+    }
+}